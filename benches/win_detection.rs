@@ -0,0 +1,50 @@
+//! Compare win detection of the bitboard with scanning the whole board, as the board did
+//! before it had a bitboard, and with scanning the tiles around the last move.
+use connect4::bench::Position;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Random positions on the standard board, from a few moves in to finished games.
+fn positions(count: usize) -> Vec<Position> {
+    let mut rng = StdRng::seed_from_u64(7);
+    (0..count)
+        .map(|_| {
+            let moves = rng.gen_range(1..=42);
+            let cols = (0..moves).map(|_| rng.gen_range(1..=7)).collect::<Vec<_>>();
+            Position::play(&cols)
+        })
+        .collect()
+}
+
+fn check_win(c: &mut Criterion) {
+    let positions = positions(1000);
+    let mut group = c.benchmark_group("check_win");
+    group.bench_function("array_scan", |b| {
+        b.iter(|| {
+            positions
+                .iter()
+                .filter(|pos| black_box(pos).array_win())
+                .count()
+        })
+    });
+    group.bench_function("board_scan", |b| {
+        b.iter(|| {
+            positions
+                .iter()
+                .filter(|pos| black_box(pos).scan_win())
+                .count()
+        })
+    });
+    group.bench_function("bitboard", |b| {
+        b.iter(|| {
+            positions
+                .iter()
+                .filter(|pos| black_box(pos).bit_win())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, check_win);
+criterion_main!(benches);
@@ -0,0 +1,125 @@
+use c4_display::{DisplayInterface, LedColor, LedState, PinConfig, Running, SyncType};
+
+use connect4::{Animation, Color, DisplayPins, Error, Led, MatrixDisplay};
+
+/// The led matrix the game was built for, driven through the gpio pins.
+pub struct LedMatrix<'d, const W: usize, const H: usize> {
+    display: Option<DisplayInterface<'d, Running, W, H>>,
+}
+
+impl<'d, const W: usize, const H: usize> LedMatrix<'d, W, H> {
+    /// Start driving the led matrix, wired to the default pins and refreshed at 60 Hz.
+    pub fn new() -> Self {
+        Self::with_pins(DisplayPins::default(), 60.0)
+    }
+
+    /// Start driving the led matrix wired to the given pins, refreshed at the given rate in Hz.
+    pub fn with_pins(pins: DisplayPins, refresh_rate: f64) -> Self {
+        Self {
+            display: Some(DisplayInterface::new("connect4").start(
+                refresh_rate,
+                PinConfig {
+                    sr_serin: pins.sr_serin,
+                    sr_srclk: pins.sr_srclk,
+                    sr_rclk: pins.sr_rclk,
+                    sr_srclr: pins.sr_srclr,
+                    sr_oe: pins.sr_oe,
+                    dec_a0: pins.dec_a0,
+                    dec_a1: pins.dec_a1,
+                    dec_a2: pins.dec_a2,
+                    dec_le: pins.dec_le,
+                    dec_e1: pins.dec_e1,
+                },
+            )),
+        }
+    }
+}
+
+impl<'d, const W: usize, const H: usize> Default for LedMatrix<'d, W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'d, const W: usize, const H: usize> MatrixDisplay for LedMatrix<'d, W, H> {
+    fn sync(&mut self, frame: Vec<Vec<Led>>) -> Result<(), Error> {
+        if let Some(display) = &mut self.display {
+            let frame = frame
+                .into_iter()
+                .map(|row| row.into_iter().map(led_state).collect())
+                .collect();
+            display.sync(SyncType::All(frame)).map_err(display_error)?;
+        }
+        Ok(())
+    }
+
+    fn add_animation(&mut self, animation: Animation) -> Result<(), Error> {
+        if let Some(display) = &mut self.display {
+            let frames = animation
+                .frames
+                .into_iter()
+                .map(|frame| {
+                    let leds = frame
+                        .leds
+                        .into_iter()
+                        .map(|(x, y, led)| (x, y, led_state(led)))
+                        .collect();
+                    c4_display::AnimationFrame::new(frame.dur, leds, frame.reset)
+                })
+                .collect();
+            display
+                .add_animation(c4_display::Animation::new(
+                    animation.looping,
+                    frames,
+                    animation.repeats,
+                    animation.keep_last,
+                ))
+                .map_err(display_error)?;
+        }
+        Ok(())
+    }
+
+    fn clear_animations(&mut self) {
+        if let Some(display) = &mut self.display {
+            display.clear_animations();
+        }
+    }
+
+    fn stop(&mut self) -> Result<(), Error> {
+        if let Some(display) = self.display.take() {
+            display.stop().map_err(display_error)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'d, const W: usize, const H: usize> Drop for LedMatrix<'d, W, H> {
+    fn drop(&mut self) {
+        drop(self.stop()); // drop to discard result without warning
+    }
+}
+
+/// Wrap the error of the display driver, keeping it as the source.
+fn display_error(err: c4_display::Error) -> Error {
+    Error::Display(Box::new(err))
+}
+
+/// Convert a led to the led state of the matrix.
+fn led_state(led: Led) -> LedState {
+    LedState {
+        color: match led.color {
+            Color::Off => LedColor::default(),
+            Color::Red => LedColor::Red,
+            Color::Green => LedColor::Green,
+            Color::Blue => LedColor::Blue,
+            Color::Yellow => LedColor::Yellow,
+            Color::Cyan => LedColor::Cyan,
+            Color::Magenta => LedColor::Magenta,
+            Color::White => LedColor::White,
+        },
+        blink: led.blink.map(|blink| c4_display::BlinkInfo {
+            dur: blink.dur,
+            int: blink.int,
+        }),
+    }
+}
@@ -0,0 +1,46 @@
+//! Connect 4 on the led matrix.
+//!
+//! Usage: `connect4 [options]`, run with `--help` to list the options.
+mod led_matrix;
+
+use connect4::{
+    cli::{self, Screen},
+    Config, Error, MatrixDisplay, BOARD_SIZES,
+};
+use led_matrix::LedMatrix;
+
+/// Shows the game on the led matrix, wired to the pins of the config.
+struct Matrix;
+
+impl Matrix {
+    /// Open the led matrix for a board of `W` columns and `H - 1` playable rows.
+    fn led_matrix<const W: usize, const H: usize>(config: &Config) -> Box<dyn MatrixDisplay> {
+        Box::new(LedMatrix::<W, H>::with_pins(
+            config.display_pins,
+            config.refresh_rate,
+        ))
+    }
+}
+
+impl Screen for Matrix {
+    fn open(&self, config: &Config) -> Result<Box<dyn MatrixDisplay>, Error> {
+        match (config.width, config.height) {
+            (6, 5) => Ok(Self::led_matrix::<6, 6>(config)),
+            (7, 6) => Ok(Self::led_matrix::<7, 7>(config)),
+            (8, 7) => Ok(Self::led_matrix::<8, 8>(config)),
+            (9, 7) => Ok(Self::led_matrix::<9, 8>(config)),
+            (width, height) => Err(Error::InvalidConfig(format!(
+                "the led matrix can't show a {width}x{height} board, use one of {}",
+                BOARD_SIZES
+                    .iter()
+                    .map(|(w, h)| format!("{w}x{h}"))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ))),
+        }
+    }
+}
+
+fn main() {
+    cli::main(Matrix);
+}
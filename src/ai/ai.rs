@@ -1,59 +1,220 @@
 use rand::prelude::SliceRandom;
 
+use std::time::Duration;
+
+use super::{
+    hint::Hint,
+    mcts::Mcts,
+    solver::{Outcome, Solver},
+};
 use crate::game::{
     board::Board,
-    components::{Check4, TileType},
-    error::{Error, GameResult},
+    components::{CheckWin, TileType},
+    error::Error,
+    search_board::SearchBoard,
 };
 
+/// The difficulty levels of the ai opponent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AiLevel {
+    /// Places in a random column.
+    Random,
+    /// Only looks at the next move, taking wins and blocking immediate threats.
+    Easy,
+    /// Searches 3 moves ahead.
+    Medium,
+    /// Searches 5 moves ahead.
+    #[default]
+    Hard,
+    /// Plays perfectly on the standard 7x6 board, using an opening book for the first moves of
+    /// the first player. Searches 9 moves ahead on other boards, or when the position can't be
+    /// solved in time, which happens in the first few moves of the second player.
+    Perfect,
+    /// Plays random games for a fixed amount of time and picks the most promising column.
+    Mcts,
+}
+
+impl AiLevel {
+    /// The number of moves the ai looks ahead at this level.
+    fn depth(self) -> usize {
+        match self {
+            AiLevel::Random | AiLevel::Easy | AiLevel::Mcts => 0,
+            AiLevel::Medium => 3,
+            AiLevel::Hard => 5,
+            AiLevel::Perfect => 9,
+        }
+    }
+}
+
+impl std::str::FromStr for AiLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "random" => Ok(Self::Random),
+            "easy" => Ok(Self::Easy),
+            "medium" => Ok(Self::Medium),
+            "hard" => Ok(Self::Hard),
+            "perfect" => Ok(Self::Perfect),
+            "mcts" => Ok(Self::Mcts),
+            str => Err(Error::InvalidInput(str.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for AiLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AiLevel::Random => write!(f, "random"),
+            AiLevel::Easy => write!(f, "easy"),
+            AiLevel::Medium => write!(f, "medium"),
+            AiLevel::Hard => write!(f, "hard"),
+            AiLevel::Perfect => write!(f, "perfect"),
+            AiLevel::Mcts => write!(f, "mcts"),
+        }
+    }
+}
+
+/// Score of a won position, before adding the remaining search depth.
+const WIN_SCORE: i32 = 1_000_000;
+
 // TODO rename
 enum MoveRanking {
     WinChance(usize),
     Neutral(usize),
     OpponentWin(usize),
-    ColumnFull(usize),
+    ColumnFull,
     NoOptions,
 }
 
 /// Ai.
-pub(crate) struct Ai<const W: usize, const H: usize> {
+pub(crate) struct Ai {
     /// Reference to the game board.
-    board: Board<W, H>,
+    board: Board,
     /// Internal board, used for testing placement positions.
-    test_board: Board<W, H>,
+    test_board: SearchBoard,
+    /// Difficulty level of the ai.
+    level: AiLevel,
+    /// Number of moves the ai looks ahead. A depth of 0 uses the one move heuristic.
+    depth: usize,
+    /// Exact solver, used at the perfect level and for analysis.
+    solver: Solver,
+    /// Monte Carlo tree search player, used at the mcts level.
+    mcts: Mcts,
+    /// Time the mcts player may think per move.
+    time_budget: Duration,
+    /// Tile the ai is currently placing.
+    tile: TileType,
 }
 
-impl<const W: usize, const H: usize> Ai<W, H> {
-    /// Create a new ai.
-    pub(crate) fn new() -> GameResult<Self> {
-        Ok(Self {
-            board: Board::new()?,
-            test_board: Board::new()?,
-        })
+impl Ai {
+    /// Time the mcts player thinks per move when none is given.
+    pub(crate) const DEFAULT_TIME_BUDGET: Duration = Duration::from_millis(500);
+
+    /// Create a new ai. It plays on the size of the board it is given each move.
+    pub(crate) fn new() -> Self {
+        Self {
+            board: Board::default(),
+            test_board: SearchBoard::from(&Board::default()),
+            level: AiLevel::default(),
+            depth: AiLevel::default().depth(),
+            solver: Solver::new(),
+            mcts: Mcts::new(),
+            time_budget: Self::DEFAULT_TIME_BUDGET,
+            tile: TileType::Player2,
+        }
     }
 
-    /// Let the AI make a move.
+    /// Get the difficulty level of the ai.
+    pub(crate) fn level(&self) -> AiLevel {
+        self.level
+    }
+
+    /// Set the difficulty level of the ai.
+    pub(crate) fn set_level(&mut self, level: AiLevel) {
+        self.level = level;
+        self.depth = level.depth();
+    }
+
+    /// Create a new ai with the same settings, to suggest columns to a player.
+    ///
+    /// The random ai suggests columns at the default level instead.
+    pub(crate) fn adviser(&self) -> Self {
+        let mut ai = Self::new();
+        if self.level != AiLevel::Random {
+            ai.level = self.level;
+            ai.depth = self.depth;
+        }
+        ai.time_budget = self.time_budget;
+        ai
+    }
+
+    /// Set the number of moves the ai looks ahead, overriding the depth of the current level.
+    ///
+    /// A random ai is switched to searching at the medium level.
+    pub(crate) fn set_depth(&mut self, depth: usize) {
+        if self.level == AiLevel::Random {
+            self.level = AiLevel::Medium;
+        }
+        self.depth = depth;
+    }
+
+    /// Switch to the mcts player, thinking for the given time per move.
+    pub(crate) fn set_time_budget(&mut self, budget: Duration) {
+        self.level = AiLevel::Mcts;
+        self.time_budget = budget;
+    }
+
+    /// Let the AI make a move with the given tile.
     ///
     /// Returns the column in which the AI decides place a piece. This column is 1 indexed.
-    pub(crate) fn make_move(&mut self, board: &Board<W, H>) -> usize {
+    pub(crate) fn make_move(&mut self, board: &Board, tile: TileType) -> usize {
         // set the internal boards
         self.board = board.clone();
-        self.test_board = board.clone();
+        self.test_board = SearchBoard::from(board);
+        self.tile = tile;
+
+        if self.level == AiLevel::Random {
+            return self.random_move();
+        }
+
+        if self.level == AiLevel::Mcts {
+            let col = self.mcts.best_move(board, tile, self.time_budget);
+            log::debug!("AI: Placing at most explored column {col}");
+            return col;
+        }
+
+        if self.level == AiLevel::Perfect {
+            match self.solver.best_moves(board, tile) {
+                Some(cols) if cols.len() == 1 => {
+                    log::debug!("AI: Solved position, placing at column {}", cols[0]);
+                    return cols[0];
+                }
+                // equally good against a perfect opponent, the search picks the one most likely
+                // to trip up a weaker opponent
+                Some(cols) => return self.search(tile, &cols),
+                None => log::debug!("AI: Could not solve position, searching instead"),
+            }
+        }
 
-        // check if the ai can connect 4
-        if let Some(col) = self.check_make_4(TileType::Player2) {
-            log::debug!("AI: Making connect 4 at column {col}");
+        if self.depth > 0 {
+            return self.search(tile, &self.move_order());
+        }
+
+        // check if the ai can connect a line
+        if let Some(col) = self.check_connect(tile) {
+            log::debug!("AI: Connecting a line at column {col}");
             return col;
         }
 
-        // check if the opponent can connect 4
-        if let Some(col) = self.check_make_4(TileType::Player1) {
-            log::debug!("AI: Preventing connect 4 at column {col}");
+        // check if the opponent can connect a line
+        if let Some(col) = self.check_connect(tile.opponent()) {
+            log::debug!("AI: Preventing a line at column {col}");
             return col;
         }
 
         // place in a random spot
-        let mut columns = vec![0; W]
+        let mut columns = vec![0; self.board.width()]
             .iter()
             .enumerate()
             .map(|(i, _)| i + 1)
@@ -80,18 +241,195 @@ impl<const W: usize, const H: usize> Ai<W, H> {
         }
     }
 
-    /// Check if the given player can connect 4.
+    /// Suggest a column for the given player, at the difficulty level of the ai, and explain
+    /// why it is a good move.
+    pub(crate) fn hint(&mut self, board: &Board, tile: TileType) -> Hint {
+        let col = self.make_move(board, tile);
+        Hint::explain(board, tile, col)
+    }
+
+    /// Find the outcome of the position for the given player to move.
+    ///
+    /// Returns [None] if the board isn't the standard 7x6 size connecting 4, or could not be
+    /// solved in time.
+    pub(crate) fn solve(&mut self, board: &Board, player: TileType) -> Option<Outcome> {
+        self.solver.solve(board, player)
+    }
+
+    /// Find the outcome of placing in each column for the given player.
+    ///
+    /// Returns [None] if the board isn't the standard 7x6 size connecting 4. Columns that are
+    /// full or could not be solved in time are [None].
+    pub(crate) fn analyse(
+        &mut self,
+        board: &Board,
+        player: TileType,
+    ) -> Option<Vec<Option<Outcome>>> {
+        self.solver.analyse(board, player)
+    }
+
+    /// Pick a random column that is not full.
+    ///
+    /// Returns the column to place in. This column is 1 indexed.
+    fn random_move(&self) -> usize {
+        let columns = (1..=self.board.width())
+            .filter(|col| self.board.first_empty(col - 1).is_ok())
+            .collect::<Vec<usize>>();
+        let col = *columns
+            .choose(&mut rand::thread_rng())
+            .expect("board should have at least 1 empty column");
+        log::debug!("AI: Placing random at column {col}");
+        col
+    }
+
+    /// Search for the best of the given columns for the given player using negamax with
+    /// alpha-beta pruning. The columns are 1 indexed and searched in order.
+    ///
+    /// Returns the column to place in. This column is 1 indexed.
+    fn search(&mut self, player: TileType, columns: &[usize]) -> usize {
+        let mut alpha = -i32::MAX;
+        let mut best = None;
+
+        for &col in columns {
+            if self.test_board.place(col, player).is_err() {
+                continue;
+            }
+            let score = match self.test_board.check_win() {
+                CheckWin::Player(_) => WIN_SCORE + self.depth as i32,
+                CheckWin::Draw => 0,
+                CheckWin::None => {
+                    -self.negamax(self.depth - 1, -i32::MAX, -alpha, player.opponent())
+                }
+            };
+            drop(self.test_board.undo_last()); // drop to discard result without warning
+
+            log::trace!("AI: column {col} scored {score}");
+            if best.is_none() || score > alpha {
+                alpha = score;
+                best = Some(col);
+            }
+        }
+
+        let col = best.expect("board should have at least 1 empty column");
+        log::debug!(
+            "AI: Searched {} moves deep, placing at column {col}",
+            self.depth
+        );
+        col
+    }
+
+    /// Score the position on the test board for the player to move.
+    ///
+    /// Positive scores favour `player`, negative scores favour the opponent.
+    fn negamax(&mut self, depth: usize, mut alpha: i32, beta: i32, player: TileType) -> i32 {
+        if depth == 0 {
+            return self.evaluate(player);
+        }
+
+        let mut best = None;
+        for col in self.move_order() {
+            if self.test_board.place(col, player).is_err() {
+                continue;
+            }
+            let score = match self.test_board.check_win() {
+                CheckWin::Player(_) => WIN_SCORE + depth as i32,
+                CheckWin::Draw => 0,
+                CheckWin::None => -self.negamax(depth - 1, -beta, -alpha, player.opponent()),
+            };
+            drop(self.test_board.undo_last()); // drop to discard result without warning
+
+            best = Some(best.map_or(score, |best: i32| best.max(score)));
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        // no moves left means the board is full
+        best.unwrap_or(0)
+    }
+
+    /// Heuristic evaluation of the test board for the given player.
+    ///
+    /// Rewards lines missing one or two tiles that are not blocked by the opponent, and tiles in
+    /// the center columns. When connecting 4 these are open threes and twos.
+    fn evaluate(&self, player: TileType) -> i32 {
+        let n = self.test_board.connect();
+        const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+        let tile = |x: isize, y: isize| {
+            if x < 0 || y < 1 {
+                return None;
+            }
+            self.test_board.get(x as usize, y as usize).ok()
+        };
+
+        let (w, h) = (self.board.width(), self.board.height());
+        let mut score = 0;
+        for y in 1..h as isize {
+            for x in 0..w as isize {
+                for (dx, dy) in DIRECTIONS {
+                    let mut own = 0;
+                    let mut other = 0;
+                    for i in 0..n as isize {
+                        match tile(x + dx * i, y + dy * i) {
+                            Some(TileType::Empty) => (),
+                            Some(t) if t == player => own += 1,
+                            Some(_) => other += 1,
+                            None => {
+                                own = 0;
+                                other = 0;
+                                break;
+                            }
+                        }
+                    }
+                    // a single tile is not worth anything when connecting 3
+                    score += match (own, other) {
+                        (own, 0) if own + 1 == n => 50,
+                        (own, 0) if own + 2 == n && own > 1 => 5,
+                        (0, other) if other + 1 == n => -50,
+                        (0, other) if other + 2 == n && other > 1 => -5,
+                        _ => 0,
+                    };
+                }
+            }
+        }
+
+        // center control
+        for x in (w - 1) / 2..=w / 2 {
+            for y in 1..h {
+                match self.test_board.get(x, y) {
+                    Ok(TileType::Empty) | Err(_) => (),
+                    Ok(t) if t == player => score += 3,
+                    Ok(_) => score -= 3,
+                }
+            }
+        }
+
+        score
+    }
+
+    /// Columns ordered from the center outwards, 1 indexed.
+    ///
+    /// Center moves tend to be better, so trying them first lets alpha-beta prune more.
+    fn move_order(&self) -> Vec<usize> {
+        let w = self.board.width();
+        let mut columns = (1..=w).collect::<Vec<usize>>();
+        columns.sort_by_key(|col| (2 * col).abs_diff(w + 1));
+        columns
+    }
+
+    /// Check if the given player can connect a line to win.
     ///
     /// Returns [Some(column)](std) if yes, and [None] if no.
-    /// This is used to check if the ai can connect 4, if the opposition can connect 4,
-    /// or if a placement results in the opponent being able to connect 4.
-    fn check_make_4(&mut self, player: TileType) -> Option<usize> {
-        for col in 1..=W {
+    /// This is used to check if the ai can win, if the opposition can win,
+    /// or if a placement results in the opponent being able to win.
+    fn check_connect(&mut self, player: TileType) -> Option<usize> {
+        for col in 1..=self.board.width() {
             if let Ok(()) = self.test_board.place(col, player) {
-                match self.test_board.check4() {
-                    Check4::None => (),
-                    Check4::Draw => (),
-                    Check4::Player(_) => return Some(col),
+                match self.test_board.check_win() {
+                    CheckWin::None => (),
+                    CheckWin::Draw => (),
+                    CheckWin::Player(_) => return Some(col),
                 }
 
                 drop(self.test_board.undo_last()); // drop to discard result without warning
@@ -106,7 +444,7 @@ impl<const W: usize, const H: usize> Ai<W, H> {
     ///
     /// Returns a column to place in.
     fn rand_place(&mut self, mut positions: Vec<usize>) -> MoveRanking {
-        self.test_board = self.board.clone();
+        self.test_board = SearchBoard::from(&self.board);
 
         // if no options remain
         let column = match positions.pop() {
@@ -117,19 +455,19 @@ impl<const W: usize, const H: usize> Ai<W, H> {
             }
         };
 
-        match self.test_board.place(column, TileType::Player2) {
+        match self.test_board.place(column, self.tile) {
             Ok(()) => {
-                if let Some(col) = self.check_make_4(TileType::Player2) {
+                if let Some(col) = self.check_connect(self.tile) {
                     log::trace!("AI: chance to win at {col}");
                     MoveRanking::WinChance(col)
                 } else {
-                    match self.check_make_4(TileType::Player1) {
-                        // placement results in opponent being able to connect 4 their next turn
+                    match self.check_connect(self.tile.opponent()) {
+                        // placement results in opponent being able to win their next turn
                         Some(_) => match self.rand_place(positions) {
                             MoveRanking::Neutral(col) => MoveRanking::Neutral(col),
                             _ => MoveRanking::OpponentWin(column),
                         },
-                        // placement doesn't result in opponent being able to connect 4 their next turn
+                        // placement doesn't result in opponent being able to win their next turn
                         None => MoveRanking::Neutral(column),
                     }
                 }
@@ -139,7 +477,7 @@ impl<const W: usize, const H: usize> Ai<W, H> {
                 MoveRanking::WinChance(col) => MoveRanking::WinChance(col),
                 MoveRanking::Neutral(col) => MoveRanking::Neutral(col),
                 MoveRanking::OpponentWin(col) => MoveRanking::OpponentWin(col),
-                _ => MoveRanking::ColumnFull(column),
+                _ => MoveRanking::ColumnFull,
             },
             //
             _ => unimplemented!(),
@@ -158,3 +496,48 @@ impl<const W: usize, const H: usize> Ai<W, H> {
         // }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Place in the column the ai picks, and solve the position for the opponent.
+    fn after_move(level: AiLevel, position: &str, tile: TileType) -> (usize, Option<Outcome>) {
+        let mut board = position.parse::<Board>().unwrap();
+        let mut ai = Ai::new();
+        ai.set_level(level);
+        let col = ai.make_move(&board, tile);
+        board.place(col, tile).unwrap();
+        (col, Solver::new().solve(&board, tile.opponent()))
+    }
+
+    #[test]
+    fn takes_forced_win() {
+        // placing next to the pair on the bottom makes three with both sides open, early in the
+        // game where the perfect ai searches, and later where it solves the position
+        let positions = [
+            "......./......./......./......./..oo.../..xx... x",
+            "......./......./..ox.../..xo.../..oo.../..xx... x",
+        ];
+        for position in positions {
+            for level in [AiLevel::Hard, AiLevel::Perfect] {
+                let (col, outcome) = after_move(level, position, TileType::Player1);
+                assert_eq!(outcome, Some(Outcome::Loss(1)), "{level} placed at {col}");
+            }
+        }
+    }
+
+    #[test]
+    fn wins_before_blocking() {
+        let position = "......./......./......./......./ooo..../xxx.... x";
+        for level in [
+            AiLevel::Easy,
+            AiLevel::Medium,
+            AiLevel::Hard,
+            AiLevel::Perfect,
+        ] {
+            let (col, _) = after_move(level, position, TileType::Player1);
+            assert_eq!(col, 4, "{level}");
+        }
+    }
+}
@@ -0,0 +1,177 @@
+/// Winning columns of the first player, zero indexed, for positions after 0, 2, 4 and 6 moves,
+/// which are too deep to solve in time. Sorted by position key, with only one of each pair of
+/// mirrored positions.
+///
+/// Placing in the center first is a known win. The other entries are printed by the ignored
+/// `print_opening_book` test of the solver.
+pub(super) const BOOK: [(u64, u8); 169] = [
+    (0, 3),
+    (4194305, 3),
+    (4194432, 1),
+    (4194945, 2),
+    (4195456, 3),
+    (4210688, 5),
+    (4211328, 4),
+    (4227715, 2),
+    (4228225, 3),
+    (4260481, 2),
+    (8388608, 3),
+    (8389248, 3),
+    (8422017, 3),
+    (12582915, 3),
+    (12583041, 3),
+    (12584065, 3),
+    (12585088, 3),
+    (12599297, 3),
+    (12600448, 3),
+    (20971521, 3),
+    (20972672, 3),
+    (25165825, 3),
+    (25165952, 3),
+    (25166465, 3),
+    (25166976, 3),
+    (25182208, 3),
+    (25182848, 3),
+    (29360135, 3),
+    (29360259, 3),
+    (29360513, 3),
+    (29376515, 3),
+    (29376641, 3),
+    (29409281, 3),
+    (41943040, 3),
+    (41943680, 3),
+    (46137347, 3),
+    (46137473, 3),
+    (46153729, 3),
+    (54525955, 3),
+    (54526081, 3),
+    (54542337, 3),
+    (58720259, 3),
+    (58720385, 3),
+    (58720640, 3),
+    (58736641, 3),
+    (58736768, 3),
+    (58769408, 3),
+    (88080385, 4),
+    (92274689, 2),
+    (92274816, 3),
+    (92291072, 2),
+    (109051905, 2),
+    (109052032, 1),
+    (109068288, 2),
+    (176160768, 2),
+    (272630017, 1),
+    (272630272, 0),
+    (272630276, 3),
+    (272630400, 3),
+    (272630531, 4),
+    (272630786, 3),
+    (272631041, 3),
+    (272646400, 3),
+    (272646658, 4),
+    (272646913, 3),
+    (272663169, 3),
+    (276824320, 3),
+    (276824578, 3),
+    (276824833, 3),
+    (281018369, 3),
+    (281019009, 3),
+    (281019520, 3),
+    (281035009, 3),
+    (281035264, 3),
+    (281035392, 3),
+    (281067776, 3),
+    (289407616, 3),
+    (289423616, 3),
+    (293601537, 3),
+    (293601792, 3),
+    (293601920, 3),
+    (293617920, 3),
+    (297795587, 3),
+    (297795713, 3),
+    (297811969, 3),
+    (310378752, 3),
+    (314572801, 3),
+    (322961409, 3),
+    (327155713, 3),
+    (327155840, 3),
+    (327172096, 3),
+    (541082241, 3),
+    (541082752, 3),
+    (541115008, 2),
+    (545276544, 3),
+    (809500928, 4),
+    (809501186, 3),
+    (809501441, 4),
+    (817889920, 3),
+    (817905920, 3),
+    (830472448, 3),
+    (834666497, 3),
+    (1077953152, 3),
+    (2956984577, 1),
+    (2956984832, 2),
+    (2957000960, 3),
+    (2961178880, 3),
+    (5104468224, 3),
+    (34363933312, 3),
+    (34363966081, 2),
+    (34372321281, 3),
+    (34372321921, 3),
+    (34372322432, 3),
+    (34372338304, 3),
+    (34380710528, 3),
+    (34384904832, 3),
+    (34389098499, 3),
+    (34389098625, 3),
+    (34389114881, 3),
+    (34405875713, 3),
+    (34414264321, 3),
+    (34418458625, 3),
+    (34418458752, 3),
+    (34632368384, 3),
+    (34632368642, 3),
+    (34632368897, 3),
+    (34640756993, 3),
+    (34640757248, 3),
+    (34640757376, 3),
+    (34640773376, 3),
+    (34649145600, 3),
+    (34653339904, 3),
+    (34657533953, 3),
+    (34900820608, 2),
+    (35177627904, 3),
+    (37316722944, 3),
+    (68723687425, 3),
+    (68723851265, 3),
+    (68732076035, 3),
+    (68732076161, 3),
+    (68732076416, 3),
+    (68732108801, 3),
+    (68740464641, 3),
+    (68744658945, 3),
+    (69000511489, 3),
+    (103091798656, 3),
+    (103108575233, 3),
+    (137451552769, 3),
+    (171802886145, 3),
+    (171811274755, 3),
+    (171811274881, 3),
+    (171811291137, 3),
+    (171819663361, 3),
+    (171823857665, 3),
+    (172071354369, 4),
+    (172079710209, 3),
+    (309250228225, 3),
+    (4398050738817, 3),
+    (4398059094017, 3),
+    (4398059094657, 3),
+    (4398075871235, 3),
+    (4398075871361, 3),
+    (4398075887617, 3),
+    (4398092648449, 3),
+    (4398101037057, 3),
+    (4398105231361, 3),
+    (4398319141378, 3),
+    (4398319141633, 3),
+    (4398327529729, 3),
+];
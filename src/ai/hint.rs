@@ -0,0 +1,186 @@
+use crate::game::{
+    board::Board,
+    components::{CheckWin, Line, TileType},
+};
+
+/// Direction of a line on the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    Horizontal,
+    Vertical,
+    Diagonal,
+}
+
+impl Direction {
+    /// Get the direction of a line of at least 2 tiles.
+    fn of(line: &Line) -> Self {
+        let ((y1, x1), (y2, x2)) = (line[0], line[1]);
+        if x1 == x2 {
+            Self::Vertical
+        } else if y1 == y2 {
+            Self::Horizontal
+        } else {
+            Self::Diagonal
+        }
+    }
+}
+
+/// Why the ai suggests a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Reason {
+    /// The tile connects a line, winning the game.
+    Wins,
+    /// The tile keeps the opponent from connecting a line in the given direction.
+    Blocks(Direction),
+    /// The tile lets the player connect a line in two new columns, so only one can be blocked.
+    DoubleThreat,
+    /// The tile lets the player connect a line in the given direction in a new column on the
+    /// next move.
+    Threat(Direction),
+    /// Every other column lets the opponent connect a line on their next move.
+    OnlySafe,
+    /// Nothing stands out, the column scored best in the search of the ai.
+    Best,
+}
+
+/// A column suggested by the ai, and why.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Hint {
+    /// The suggested column, 1 indexed.
+    pub(crate) col: usize,
+    pub(crate) reason: Reason,
+}
+
+impl Hint {
+    /// Explain why placing a tile in the column is a good move for the given player. The
+    /// column is 1 indexed and must not be full.
+    ///
+    /// Only drops are looked at, also when playing by the PopOut rules.
+    pub(crate) fn explain(board: &Board, tile: TileType, col: usize) -> Self {
+        let reason = if Self::winning_line(board, tile, col).is_some() {
+            Reason::Wins
+        } else if let Some(line) = Self::winning_line(board, tile.opponent(), col) {
+            Reason::Blocks(Direction::of(&line))
+        } else {
+            let mut after = board.clone();
+            drop(after.place(col, tile)); // drop to discard result without warning
+
+            // only count threats the tile creates, not the ones the player already had
+            let threats = (1..=board.width())
+                .filter(|&col| Self::winning_line(board, tile, col).is_none())
+                .filter_map(|col| Self::winning_line(&after, tile, col))
+                .collect::<Vec<Line>>();
+            match threats.as_slice() {
+                [_, _, ..] => Reason::DoubleThreat,
+                [line] => Reason::Threat(Direction::of(line)),
+                [] if Self::only_safe(board, tile, col) => Reason::OnlySafe,
+                [] => Reason::Best,
+            }
+        };
+        Self { col, reason }
+    }
+
+    /// Get the first line the player connects by placing in the column, or [None] if the
+    /// player doesn't win there.
+    fn winning_line(board: &Board, tile: TileType, col: usize) -> Option<Line> {
+        let mut test = board.clone();
+        test.place(col, tile).ok()?;
+        match test.check_win() {
+            CheckWin::Player(mut lines) => Some(lines.swap_remove(0)),
+            CheckWin::Draw | CheckWin::None => None,
+        }
+    }
+
+    /// Check if the column is the only one where the player doesn't give the opponent a line
+    /// to connect on their next move.
+    fn only_safe(board: &Board, tile: TileType, col: usize) -> bool {
+        // if the opponent can win after placing in each of the other columns
+        let others = (1..=board.width())
+            .filter(|&other| other != col)
+            .filter_map(|other| {
+                let mut test = board.clone();
+                test.place(other, tile).ok()?;
+                Some(
+                    (1..=board.width())
+                        .any(|next| Self::winning_line(&test, tile.opponent(), next).is_some()),
+                )
+            })
+            .collect::<Vec<bool>>();
+        !others.is_empty() && others.iter().all(|&loses| loses)
+    }
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Horizontal => write!(f, "horizontal"),
+            Direction::Vertical => write!(f, "vertical"),
+            Direction::Diagonal => write!(f, "diagonal"),
+        }
+    }
+}
+
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::Wins => write!(f, "connects a line and wins"),
+            Reason::Blocks(direction) => write!(f, "blocks a {direction} threat"),
+            Reason::DoubleThreat => write!(f, "creates a double threat"),
+            Reason::Threat(direction) => write!(f, "creates a {direction} threat"),
+            Reason::OnlySafe => write!(f, "every other column lets the opponent win"),
+            Reason::Best => write!(f, "looks strongest to the ai"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Explain placing in the column for player 1, who is to move in the position.
+    fn reason(position: &str, col: usize) -> Reason {
+        let board = position.parse::<Board>().unwrap();
+        Hint::explain(&board, TileType::Player1, col).reason
+    }
+
+    #[test]
+    fn wins() {
+        let position = "......./......./......./......./ooo..../xxx.... x";
+        assert_eq!(reason(position, 4), Reason::Wins);
+    }
+
+    #[test]
+    fn blocks() {
+        let position = "......./......./......./......./xx...../ooo.x.. x";
+        assert_eq!(reason(position, 4), Reason::Blocks(Direction::Horizontal));
+        let position = "......./......./o....../o....../o....../xx.x... x";
+        assert_eq!(reason(position, 1), Reason::Blocks(Direction::Vertical));
+    }
+
+    #[test]
+    fn threats() {
+        let position = "......./......./......./......./......./.xx.o.o x";
+        assert_eq!(reason(position, 4), Reason::Threat(Direction::Horizontal));
+        let position = "......./......./......./......./......o/..xx..o x";
+        assert_eq!(reason(position, 5), Reason::DoubleThreat);
+    }
+
+    #[test]
+    fn only_counts_new_threats() {
+        // both players can already connect in column 4
+        let position = "......./......./......./......./......./xxx.ooo x";
+        assert_eq!(reason(position, 1), Reason::Best);
+    }
+
+    #[test]
+    fn only_safe() {
+        let position = "..oxo.o/.xxox.x/.ooox.o/.xoxo.o/xxxoo.x/xoxoxox x";
+        assert_eq!(reason(position, 2), Reason::OnlySafe);
+    }
+
+    #[test]
+    fn best() {
+        let position = "......./......./......./......./......./....... x";
+        assert_eq!(reason(position, 4), Reason::Best);
+    }
+}
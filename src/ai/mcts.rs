@@ -0,0 +1,228 @@
+use std::time::{Duration, Instant};
+
+use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+
+use crate::game::{
+    board::Board,
+    components::{CheckWin, TileType},
+    search_board::SearchBoard,
+};
+
+/// Exploration constant of the UCT formula.
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+/// A node in the search tree.
+struct Node {
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Column of the move leading to this node. This column is 1 indexed.
+    col: usize,
+    /// Tile of the player that made the move leading to this node.
+    tile: TileType,
+    /// Moves that have not been expanded yet.
+    untried: Vec<usize>,
+    visits: u32,
+    /// Wins for `tile`, draws counting as half a win.
+    score: f64,
+}
+
+/// Monte Carlo tree search player.
+///
+/// Plays random games from the current position for a fixed amount of time, and picks the most
+/// explored column. Only needs legal moves and win detection, so it works on any board size.
+pub(crate) struct Mcts {
+    /// Board the playouts are made on. Moves are undone after every playout.
+    board: SearchBoard,
+    /// Search tree, the root being the first node.
+    nodes: Vec<Node>,
+    rng: ThreadRng,
+}
+
+impl Mcts {
+    /// Create a new mcts player.
+    pub(crate) fn new() -> Self {
+        Self {
+            board: SearchBoard::from(&Board::default()),
+            nodes: Vec::new(),
+            rng: rand::thread_rng(),
+        }
+    }
+
+    /// Search for the best move for the given player within the time budget.
+    ///
+    /// Returns the column to place in. This column is 1 indexed.
+    pub(crate) fn best_move(&mut self, board: &Board, player: TileType, budget: Duration) -> usize {
+        let start = Instant::now();
+        self.board = SearchBoard::from(board);
+        self.nodes.clear();
+        let untried = self.legal_moves();
+        self.nodes.push(Node {
+            parent: None,
+            children: Vec::new(),
+            col: 0,
+            tile: player.opponent(),
+            untried,
+            visits: 0,
+            score: 0.0,
+        });
+
+        let mut playouts = 0;
+        while start.elapsed() < budget {
+            self.playout();
+            playouts += 1;
+        }
+
+        let best = self.nodes[0]
+            .children
+            .iter()
+            .max_by_key(|&&child| self.nodes[child].visits)
+            .map(|&child| self.nodes[child].col);
+        log::debug!("AI: Ran {playouts} playouts");
+
+        // no playouts finished within the budget
+        best.unwrap_or_else(|| {
+            *self.nodes[0]
+                .untried
+                .first()
+                .expect("board should have at least 1 empty column")
+        })
+    }
+
+    /// Run a single iteration: select a node, expand it, play a random game and update the
+    /// statistics of the selected nodes.
+    fn playout(&mut self) {
+        let mut placed = 0;
+        let mut node = 0;
+        let mut result = CheckWin::None;
+
+        // select
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            node = self.select_child(node);
+            let (col, tile) = (self.nodes[node].col, self.nodes[node].tile);
+            drop(self.board.place(col, tile));
+            placed += 1;
+            result = self.board.check_win();
+        }
+
+        // expand
+        if matches!(result, CheckWin::None) && !self.nodes[node].untried.is_empty() {
+            let i = self.rng.gen_range(0..self.nodes[node].untried.len());
+            let col = self.nodes[node].untried.swap_remove(i);
+            let tile = self.nodes[node].tile.opponent();
+            drop(self.board.place(col, tile));
+            placed += 1;
+            result = self.board.check_win();
+
+            let untried = match result {
+                CheckWin::None => self.legal_moves(),
+                _ => Vec::new(),
+            };
+            self.nodes.push(Node {
+                parent: Some(node),
+                children: Vec::new(),
+                col,
+                tile,
+                untried,
+                visits: 0,
+                score: 0.0,
+            });
+            let child = self.nodes.len() - 1;
+            self.nodes[node].children.push(child);
+            node = child;
+        }
+
+        // simulate
+        let mut tile = self.nodes[node].tile;
+        while matches!(result, CheckWin::None) {
+            tile = tile.opponent();
+            let col = *self
+                .legal_moves()
+                .choose(&mut self.rng)
+                .expect("board should have at least 1 empty column");
+            drop(self.board.place(col, tile));
+            placed += 1;
+            result = self.board.check_win();
+        }
+        let winner = match result {
+            CheckWin::Player(_) => Some(tile),
+            _ => None,
+        };
+
+        // backpropagate
+        let mut current = Some(node);
+        while let Some(i) = current {
+            let node = &mut self.nodes[i];
+            node.visits += 1;
+            node.score += match winner {
+                Some(winner) if winner == node.tile => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+            current = node.parent;
+        }
+
+        for _ in 0..placed {
+            drop(self.board.undo_last()); // drop to discard result without warning
+        }
+    }
+
+    /// Select the child with the highest upper confidence bound.
+    fn select_child(&self, node: usize) -> usize {
+        let ln_visits = (self.nodes[node].visits as f64).ln();
+        let uct = |child: usize| {
+            let child = &self.nodes[child];
+            let visits = child.visits as f64;
+            child.score / visits + EXPLORATION * (ln_visits / visits).sqrt()
+        };
+
+        *self.nodes[node]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| uct(a).total_cmp(&uct(b)))
+            .expect("node should have children")
+    }
+
+    /// Get the columns of the board that are not full. These columns are 1 indexed.
+    fn legal_moves(&self) -> Vec<usize> {
+        (1..=self.board.width())
+            .filter(|col| self.board.first_empty(col - 1).is_ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Search the position for the player on move within a small time budget.
+    fn best_move(position: &str) -> usize {
+        let board = position.parse::<Board>().unwrap();
+        let mut mcts = Mcts::new();
+        mcts.best_move(&board, board.to_move(), Duration::from_millis(200))
+    }
+
+    #[test]
+    fn takes_immediate_win() {
+        // both players can connect four, the player on move wins first
+        assert_eq!(
+            best_move("......./......./......./......./ooo..../xxx.... x"),
+            4
+        );
+        assert_eq!(
+            best_move("......./......./o....../o....../o....../x.xx.x. o"),
+            1
+        );
+    }
+
+    #[test]
+    fn blocks_immediate_loss() {
+        assert_eq!(
+            best_move("......./......./......./......./o....../xxx.o.. o"),
+            4
+        );
+        assert_eq!(
+            best_move("......./......./......./......o/x.....o/x.x...o x"),
+            7
+        );
+    }
+}
@@ -1,2 +1,8 @@
+#[allow(clippy::module_inception)]
 mod ai;
+mod book;
+mod hint;
+mod mcts;
+mod solver;
+pub use ai::AiLevel;
 pub(super) use ai::*;
@@ -0,0 +1,634 @@
+use super::book::BOOK;
+use crate::game::{board::Board, components::TileType};
+
+/// Number of columns of the standard board.
+const WIDTH: usize = 7;
+/// Number of playable rows of the standard board.
+const HEIGHT: usize = 6;
+/// Positions with fewer tiles take more nodes than the limit allows, unless they are in the
+/// opening book.
+const MIN_SOLVED_MOVES: usize = 7;
+/// Lowest possible score of a position.
+const MIN_SCORE: i32 = -((WIDTH * HEIGHT) as i32) / 2 + 3;
+/// Number of entries in the transposition table. Must be a prime for the keys to stay unique.
+const TABLE_SIZE: usize = 1_048_573;
+
+/// The game theoretic value of a position for the player to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    /// The player wins with their n-th move from now.
+    Win(usize),
+    /// The opponent wins with their n-th move from now.
+    Loss(usize),
+    /// Neither player can force a win.
+    Draw,
+}
+
+/// Solver for the standard 7x6 board.
+///
+/// Finds the exact outcome of a position with a negamax search on a bitboard, ordering moves by
+/// the number of threats they create, and caching results in a transposition table.
+pub(crate) struct Solver {
+    /// Lower 32 bits of the position keys in the transposition table.
+    keys: Vec<u32>,
+    /// Upper bounds of the position scores in the transposition table, 0 meaning empty.
+    values: Vec<u8>,
+    /// Number of positions searched since the budget was last reset.
+    nodes: u64,
+    /// Maximum number of positions to search before giving up.
+    node_limit: u64,
+}
+
+impl Solver {
+    /// Default number of positions searched before giving up.
+    pub(crate) const DEFAULT_NODE_LIMIT: u64 = 50_000_000;
+
+    /// Create a new solver. The transposition table is only allocated once it is needed.
+    pub(crate) fn new() -> Self {
+        Self {
+            keys: Vec::new(),
+            values: Vec::new(),
+            nodes: 0,
+            node_limit: Self::DEFAULT_NODE_LIMIT,
+        }
+    }
+
+    /// Find the outcome of the position for the given player to move.
+    ///
+    /// Returns [None] if the board isn't the standard size or the position could not be solved
+    /// within the node limit.
+    pub(crate) fn solve(&mut self, board: &Board, player: TileType) -> Option<Outcome> {
+        let pos = Position::from_board(board, player)?;
+        self.nodes = 0;
+        let score = self.score(&pos)?;
+        Some(pos.outcome(score))
+    }
+
+    /// Find the best columns to place in for the given player, center columns first. The
+    /// columns are one indexed.
+    ///
+    /// A won position gives the single column winning fastest, or the column from the opening
+    /// book. A drawn position gives every column keeping the draw. In a lost position every
+    /// column loses against a perfect opponent, so all columns that don't lose on the next move
+    /// are given.
+    ///
+    /// Returns [None] if the board isn't the standard size or the position could not be solved
+    /// within the node limit.
+    pub(crate) fn best_moves(&mut self, board: &Board, player: TileType) -> Option<Vec<usize>> {
+        let pos = Position::from_board(board, player)?;
+        self.nodes = 0;
+
+        if let Some(col) = (0..WIDTH).find(|&col| pos.is_winning_move(col)) {
+            return Some(vec![col + 1]);
+        }
+        if let Some(col) = pos.book_move() {
+            return Some(vec![col + 1]);
+        }
+        if pos.moves < MIN_SOLVED_MOVES {
+            return None;
+        }
+
+        let playable = Position::column_order()
+            .into_iter()
+            .filter(|&col| pos.can_play(col));
+        let score = self.score(&pos)?;
+        if score < 0 {
+            let safe = pos.non_losing_moves();
+            let cols = match playable
+                .clone()
+                .filter(|&col| safe & Position::column_mask(col) != 0)
+                .collect::<Vec<usize>>()
+            {
+                cols if cols.is_empty() => playable.collect(),
+                cols => cols,
+            };
+            return Some(cols.into_iter().map(|col| col + 1).collect());
+        }
+
+        // no column scores better than the position, so a null window tells the best ones apart
+        let mut best = Vec::new();
+        for col in playable {
+            let next = pos.play_col(col);
+            if !next.can_win_next() && self.negamax(&next, -score, -score + 1)? <= -score {
+                best.push(col + 1);
+                if score > 0 {
+                    break;
+                }
+            }
+        }
+        Some(best)
+    }
+
+    /// Find the outcome of placing in each column for the given player.
+    ///
+    /// Returns [None] if the board isn't the standard size. Columns that are full or that
+    /// could not be solved within the node limit are [None], the columns share the limit.
+    pub(crate) fn analyse(
+        &mut self,
+        board: &Board,
+        player: TileType,
+    ) -> Option<Vec<Option<Outcome>>> {
+        let pos = Position::from_board(board, player)?;
+        self.nodes = 0;
+
+        Some(
+            (0..WIDTH)
+                .map(|col| {
+                    if !pos.can_play(col) {
+                        return None;
+                    }
+                    if pos.is_winning_move(col) {
+                        return Some(Outcome::Win(1));
+                    }
+                    let next = pos.play_col(col);
+                    let score = self.score(&next)?;
+                    Some(match next.outcome(score) {
+                        Outcome::Win(n) => Outcome::Loss(n),
+                        Outcome::Loss(n) => Outcome::Win(n + 1),
+                        Outcome::Draw => Outcome::Draw,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Compute the exact score of a position.
+    ///
+    /// Iteratively deepens over the score range: null window searches first test scores near
+    /// the extremes, so short wins and losses are found with shallow searches before the full
+    /// range is explored.
+    fn score(&mut self, pos: &Position) -> Option<i32> {
+        if self.keys.is_empty() {
+            self.keys = vec![0; TABLE_SIZE];
+            self.values = vec![0; TABLE_SIZE];
+        }
+
+        if pos.can_win_next() {
+            return Some(((WIDTH * HEIGHT + 1 - pos.moves) / 2) as i32);
+        }
+
+        let mut min = -((WIDTH * HEIGHT - pos.moves) as i32) / 2;
+        let mut max = ((WIDTH * HEIGHT + 1 - pos.moves) / 2) as i32;
+        while min < max {
+            let mut med = min + (max - min) / 2;
+            if med <= 0 && min / 2 < med {
+                med = min / 2;
+            } else if med >= 0 && max / 2 > med {
+                med = max / 2;
+            }
+            let score = self.negamax(pos, med, med + 1)?;
+            if score <= med {
+                max = score;
+            } else {
+                min = score;
+            }
+        }
+        Some(min)
+    }
+
+    /// Negamax search with alpha-beta pruning.
+    ///
+    /// The position must not be won by the player to move in one move.
+    /// Returns [None] if the node limit was reached.
+    fn negamax(&mut self, pos: &Position, mut alpha: i32, mut beta: i32) -> Option<i32> {
+        self.nodes += 1;
+        if self.nodes > self.node_limit {
+            return None;
+        }
+
+        let next = pos.non_losing_moves();
+        if next == 0 {
+            // every move lets the opponent win
+            return Some(-((WIDTH * HEIGHT - pos.moves) as i32) / 2);
+        }
+        if pos.moves >= WIDTH * HEIGHT - 2 {
+            return Some(0);
+        }
+
+        let min = -((WIDTH * HEIGHT - 2 - pos.moves) as i32) / 2;
+        if alpha < min {
+            alpha = min;
+            if alpha >= beta {
+                return Some(alpha);
+            }
+        }
+
+        let max = match self.get(pos.key()) {
+            0 => ((WIDTH * HEIGHT - 1 - pos.moves) / 2) as i32,
+            val => val as i32 + MIN_SCORE - 1,
+        };
+        if beta > max {
+            beta = max;
+            if alpha >= beta {
+                return Some(beta);
+            }
+        }
+
+        // moves creating the most threats first, center columns first on ties
+        let mut moves = Vec::with_capacity(WIDTH);
+        for col in Position::column_order() {
+            let mv = next & Position::column_mask(col);
+            if mv != 0 {
+                moves.push((mv, pos.move_score(mv)));
+            }
+        }
+        moves.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+
+        for (mv, _) in moves {
+            let score = -self.negamax(&pos.play(mv), -beta, -alpha)?;
+            if score >= beta {
+                return Some(score);
+            }
+            if score > alpha {
+                alpha = score;
+            }
+        }
+
+        self.put(pos.key(), (alpha - MIN_SCORE + 1) as u8);
+        Some(alpha)
+    }
+
+    /// Get the stored value of a position, 0 if there is none.
+    fn get(&self, key: u64) -> u8 {
+        let i = (key % TABLE_SIZE as u64) as usize;
+        if self.keys[i] == key as u32 {
+            self.values[i]
+        } else {
+            0
+        }
+    }
+
+    /// Store the value of a position, replacing any previous entry in its slot.
+    fn put(&mut self, key: u64, value: u8) {
+        let i = (key % TABLE_SIZE as u64) as usize;
+        self.keys[i] = key as u32;
+        self.values[i] = value;
+    }
+}
+
+/// Bitboard position of the standard board, seen from the player to move.
+///
+/// Every column takes up `HEIGHT + 1` bits, the lowest bit being the bottom row.
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    /// Tiles of the player to move.
+    current: u64,
+    /// All tiles on the board.
+    mask: u64,
+    /// Number of tiles on the board.
+    moves: usize,
+}
+
+impl Position {
+    /// Convert a board to a position, with the given player to move.
+    ///
+    /// Returns [None] if the board isn't the standard size, or doesn't connect 4.
+    fn from_board(board: &Board, player: TileType) -> Option<Self> {
+        // row 0 of the board is the status bar
+        if board.width() != WIDTH || board.height() != HEIGHT + 1 || board.connect() != 4 {
+            return None;
+        }
+
+        let mut pos = Self {
+            current: 0,
+            mask: 0,
+            moves: 0,
+        };
+        for x in 0..WIDTH {
+            for row in 0..HEIGHT {
+                let tile = board.get(x, HEIGHT - row).ok()?;
+                if tile == TileType::Empty {
+                    break;
+                }
+                let bit = 1 << (x * (HEIGHT + 1) + row);
+                pos.mask |= bit;
+                if tile == player {
+                    pos.current |= bit;
+                }
+                pos.moves += 1;
+            }
+        }
+        Some(pos)
+    }
+
+    /// Convert a score to an outcome for the player to move.
+    fn outcome(&self, score: i32) -> Outcome {
+        // a score of s means the game is won after 42 - 2s or 43 - 2s moves
+        let end = |score: i32, parity: usize| {
+            let end = (WIDTH * HEIGHT + 1) - 2 * score as usize;
+            end - (end + parity) % 2
+        };
+        match score {
+            0 => Outcome::Draw,
+            s if s > 0 => Outcome::Win((end(s, self.moves) - self.moves) / 2 + 1),
+            s => Outcome::Loss((end(-s, self.moves + 1) - self.moves - 1) / 2 + 1),
+        }
+    }
+
+    /// Columns ordered from the center outwards, zero indexed.
+    fn column_order() -> [usize; WIDTH] {
+        [3, 2, 4, 1, 5, 0, 6]
+    }
+
+    /// Unique key of the position.
+    fn key(&self) -> u64 {
+        self.current + self.mask
+    }
+
+    /// Key of the position mirrored from left to right.
+    fn mirrored_key(&self) -> u64 {
+        let mirror = |bits: u64| {
+            (0..WIDTH).fold(0, |mirrored, col| {
+                let column = bits >> (col * (HEIGHT + 1)) & ((1 << (HEIGHT + 1)) - 1);
+                mirrored | column << ((WIDTH - 1 - col) * (HEIGHT + 1))
+            })
+        };
+        mirror(self.current) + mirror(self.mask)
+    }
+
+    /// Get the winning column of the opening book for the position, zero indexed.
+    fn book_move(&self) -> Option<usize> {
+        let find = |key: u64| {
+            BOOK.binary_search_by_key(&key, |&(key, _)| key)
+                .ok()
+                .map(|i| BOOK[i].1 as usize)
+        };
+        find(self.key()).or_else(|| find(self.mirrored_key()).map(|col| WIDTH - 1 - col))
+    }
+
+    fn can_play(&self, col: usize) -> bool {
+        self.mask & Self::top_mask(col) == 0
+    }
+
+    fn play(&self, mv: u64) -> Self {
+        Self {
+            current: self.current ^ self.mask,
+            mask: self.mask | mv,
+            moves: self.moves + 1,
+        }
+    }
+
+    fn play_col(&self, col: usize) -> Self {
+        self.play((self.mask + Self::bottom_mask(col)) & Self::column_mask(col))
+    }
+
+    fn is_winning_move(&self, col: usize) -> bool {
+        self.winning_spots() & self.possible() & Self::column_mask(col) != 0
+    }
+
+    fn can_win_next(&self) -> bool {
+        self.winning_spots() & self.possible() != 0
+    }
+
+    /// Moves that don't let the opponent win on their next turn.
+    fn non_losing_moves(&self) -> u64 {
+        let mut possible = self.possible();
+        let opponent_win = Self::winning_spots_of(self.current ^ self.mask, self.mask);
+        let forced = possible & opponent_win;
+        if forced != 0 {
+            if forced & (forced - 1) != 0 {
+                // the opponent has two threats, only one can be blocked
+                return 0;
+            }
+            possible = forced;
+        }
+        // don't play below a spot the opponent wins with
+        possible & !(opponent_win >> 1)
+    }
+
+    /// Number of winning spots the player to move has after the move.
+    fn move_score(&self, mv: u64) -> u32 {
+        Self::winning_spots_of(self.current | mv, self.mask).count_ones()
+    }
+
+    /// Spots the player to move can play in.
+    fn possible(&self) -> u64 {
+        (self.mask + Self::bottom()) & Self::board_mask()
+    }
+
+    /// Empty spots that would complete a line for the player to move.
+    fn winning_spots(&self) -> u64 {
+        Self::winning_spots_of(self.current, self.mask)
+    }
+
+    /// Empty spots that would complete a line for the given tiles.
+    fn winning_spots_of(tiles: u64, mask: u64) -> u64 {
+        // vertical
+        let mut spots = (tiles << 1) & (tiles << 2) & (tiles << 3);
+
+        // horizontal and both diagonals
+        for shift in [HEIGHT + 1, HEIGHT, HEIGHT + 2] {
+            let pair = (tiles << shift) & (tiles << (2 * shift));
+            spots |= pair & (tiles << (3 * shift));
+            spots |= pair & (tiles >> shift);
+            let pair = (tiles >> shift) & (tiles >> (2 * shift));
+            spots |= pair & (tiles << shift);
+            spots |= pair & (tiles >> (3 * shift));
+        }
+
+        spots & (Self::board_mask() ^ mask)
+    }
+
+    fn top_mask(col: usize) -> u64 {
+        1 << (HEIGHT - 1 + col * (HEIGHT + 1))
+    }
+
+    fn bottom_mask(col: usize) -> u64 {
+        1 << (col * (HEIGHT + 1))
+    }
+
+    fn column_mask(col: usize) -> u64 {
+        ((1 << HEIGHT) - 1) << (col * (HEIGHT + 1))
+    }
+
+    /// Bottom spot of every column.
+    fn bottom() -> u64 {
+        (0..WIDTH).fold(0, |bottom, col| bottom | Self::bottom_mask(col))
+    }
+
+    /// All playable spots.
+    fn board_mask() -> u64 {
+        Self::bottom() * ((1 << HEIGHT) - 1)
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Win(1) => write!(f, "win"),
+            Outcome::Win(n) => write!(f, "win in {n} moves"),
+            Outcome::Loss(1) => write!(f, "loss next move"),
+            Outcome::Loss(n) => write!(f, "loss in {n} moves"),
+            Outcome::Draw => write!(f, "draw"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    const EMPTY: Position = Position {
+        current: 0,
+        mask: 0,
+        moves: 0,
+    };
+
+    fn board(position: &str) -> Board {
+        position.parse().unwrap()
+    }
+
+    /// Score of the position by searching every move until the end of the game.
+    fn brute_force(pos: &Position, mut alpha: i32, beta: i32) -> i32 {
+        if pos.can_win_next() {
+            return ((WIDTH * HEIGHT + 1 - pos.moves) / 2) as i32;
+        }
+        if pos.moves == WIDTH * HEIGHT {
+            return 0;
+        }
+        for col in (0..WIDTH).filter(|&col| pos.can_play(col)) {
+            alpha = alpha.max(-brute_force(&pos.play_col(col), -beta, -alpha));
+            if alpha >= beta {
+                break;
+            }
+        }
+        alpha
+    }
+
+    #[test]
+    fn solves_known_positions() {
+        let mut solver = Solver::new();
+        let solve = |solver: &mut Solver, position, player| solver.solve(&board(position), player);
+
+        // three in a row on the bottom
+        let position = "......./......./......./......./ooo..../xxx.... x";
+        assert_eq!(
+            solve(&mut solver, position, TileType::Player1),
+            Some(Outcome::Win(1))
+        );
+        // the opponent can complete the bottom row on both sides
+        let position = "......./......./......./......./.oo..../.xxx... o";
+        assert_eq!(
+            solve(&mut solver, position, TileType::Player2),
+            Some(Outcome::Loss(1))
+        );
+        // placing next to the pair makes three with both sides open
+        let position = "......./......./..ox.../..xo.../..oo.../..xx... x";
+        assert_eq!(
+            solve(&mut solver, position, TileType::Player1),
+            Some(Outcome::Win(2))
+        );
+        assert_eq!(
+            solver.best_moves(&board(position), TileType::Player1),
+            Some(vec![5])
+        );
+        // every column loses right away, so all of them are as good
+        let position = "......./......./..ox.../..xo.../..oo.../.xxx... o";
+        assert_eq!(
+            solver.best_moves(&board(position), TileType::Player2),
+            Some(vec![4, 3, 5, 2, 6, 1, 7])
+        );
+
+        // only the standard board is solved
+        let board = "....../....../....../....../....../...... x"
+            .parse::<Board>()
+            .unwrap();
+        assert_eq!(solver.solve(&board, TileType::Player1), None);
+    }
+
+    #[test]
+    fn agrees_with_brute_force() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut solver = Solver::new();
+        let mut solved = 0;
+        while solved < 50 {
+            // random games that are still going after 30 moves
+            let mut pos = EMPTY;
+            while pos.moves < 30 {
+                let cols = (0..WIDTH)
+                    .filter(|&col| pos.can_play(col) && !pos.is_winning_move(col))
+                    .collect::<Vec<usize>>();
+                if cols.is_empty() {
+                    break;
+                }
+                pos = pos.play_col(cols[rng.gen_range(0..cols.len())]);
+            }
+            if pos.moves < 30 {
+                continue;
+            }
+
+            let max = (WIDTH * HEIGHT) as i32;
+            assert_eq!(solver.score(&pos), Some(brute_force(&pos, -max, max)));
+            solved += 1;
+        }
+    }
+
+    #[test]
+    fn opening_book() {
+        assert_eq!(EMPTY.book_move(), Some(3));
+        assert!(BOOK.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        // mirrored positions play the mirrored column
+        for col in 0..WIDTH {
+            let pos = EMPTY.play_col(3).play_col(col);
+            let mirrored = EMPTY.play_col(3).play_col(WIDTH - 1 - col);
+            assert_eq!(pos.key(), mirrored.mirrored_key());
+            if let Some(book) = pos.book_move() {
+                assert_eq!(mirrored.book_move(), Some(WIDTH - 1 - book));
+            }
+        }
+    }
+
+    /// Find a winning column for the player to move, center columns first.
+    fn winning_move(solver: &mut Solver, pos: &Position) -> Option<usize> {
+        Position::column_order().into_iter().find(|&col| {
+            if !pos.can_play(col) {
+                return false;
+            }
+            let next = pos.play_col(col);
+            pos.is_winning_move(col)
+                || !next.can_win_next() && solver.negamax(&next, -1, 0).unwrap() < 0
+        })
+    }
+
+    /// Print the opening book, following it for the first player and trying every column for
+    /// the second player. Takes about 15 minutes in release mode.
+    #[test]
+    #[ignore]
+    fn print_opening_book() {
+        let mut solver = Solver::new();
+        solver.node_limit = u64::MAX;
+        solver.keys = vec![0; TABLE_SIZE];
+        solver.values = vec![0; TABLE_SIZE];
+
+        let mut book = vec![(EMPTY.key(), 3)];
+        let mut positions = vec![EMPTY.play_col(3)];
+        for _ in 0..3 {
+            let replies = positions
+                .iter()
+                .flat_map(|pos| {
+                    (0..WIDTH)
+                        .filter(|&col| pos.can_play(col))
+                        .map(|col| pos.play_col(col))
+                })
+                .collect::<Vec<Position>>();
+            positions.clear();
+            for pos in replies {
+                let key = pos.key().min(pos.mirrored_key());
+                if book.iter().any(|&(k, _)| k == key) {
+                    continue;
+                }
+                let col = winning_move(&mut solver, &pos).expect("the first player wins");
+                book.push(match key == pos.key() {
+                    true => (key, col as u8),
+                    false => (key, (WIDTH - 1 - col) as u8),
+                });
+                positions.push(pos.play_col(col));
+            }
+        }
+        book.sort_unstable();
+        println!("const BOOK: [(u64, u8); {}] = {book:?};", book.len());
+    }
+}
@@ -0,0 +1,126 @@
+//! Plays games between two ai configurations and reports how they compare.
+//!
+//! Usage: `tournament [games] [ai] [ai]`
+//!
+//! An ai is given as a difficulty level, optionally followed by a number: the search depth for
+//! searching levels, or the time budget in milliseconds for mcts. For example `hard`, `medium:4`
+//! or `mcts:200`. The random level takes no number.
+use std::{process::exit, time::Duration};
+
+use connect4::{AiLevel, AiPlayer, TileType};
+
+/// Size of the board the games are played on, the standard 7 columns and 6 rows.
+const WIDTH: usize = 7;
+const HEIGHT: usize = 6;
+
+/// Configuration of one of the competing ai players.
+struct Contestant {
+    spec: String,
+    level: AiLevel,
+    param: Option<u64>,
+}
+
+impl Contestant {
+    fn parse(spec: &str) -> Option<Self> {
+        let (level, param) = match spec.split_once(':') {
+            Some((level, param)) => (level.parse().ok()?, Some(param.parse().ok()?)),
+            None => (spec.parse().ok()?, None),
+        };
+        // a random ai doesn't search, so it has no depth or time budget
+        if level == AiLevel::Random && param.is_some() {
+            return None;
+        }
+        Some(Self {
+            spec: spec.to_string(),
+            level,
+            param,
+        })
+    }
+
+    fn player(&self) -> AiPlayer {
+        let mut player = AiPlayer::new(self.level);
+        match (self.level, self.param) {
+            (AiLevel::Mcts, Some(ms)) => player.set_time_budget(Duration::from_millis(ms)),
+            (_, Some(depth)) => player.set_depth(depth as usize),
+            (_, None) => (),
+        }
+        player
+    }
+}
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Warn)
+        .init();
+
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let games = match args.first().map(|games| games.parse::<usize>()) {
+        None => 100,
+        Some(Ok(games)) if games > 0 => games,
+        Some(_) => usage(),
+    };
+    let first = Contestant::parse(args.get(1).map_or("hard", |s| s)).unwrap_or_else(|| usage());
+    let second = Contestant::parse(args.get(2).map_or("easy", |s| s)).unwrap_or_else(|| usage());
+
+    println!("Playing {games} games: {} vs {}", first.spec, second.spec);
+
+    let (mut wins, mut losses, mut draws, mut moves) = (0, 0, 0, 0);
+    let mut a = first.player();
+    let mut b = second.player();
+    for game in 0..games {
+        // alternate who starts
+        let (record, first_tile) = if game % 2 == 0 {
+            (a.play_against(&mut b, WIDTH, HEIGHT), TileType::Player1)
+        } else {
+            (b.play_against(&mut a, WIDTH, HEIGHT), TileType::Player2)
+        };
+        let record = record.expect("ai moves should be valid");
+
+        moves += record.moves;
+        let result = match record.winner {
+            Some(tile) if tile == first_tile => {
+                wins += 1;
+                format!("{} wins", first.spec)
+            }
+            Some(_) => {
+                losses += 1;
+                format!("{} wins", second.spec)
+            }
+            None => {
+                draws += 1;
+                String::from("draw")
+            }
+        };
+        println!("Game {}: {result} after {} moves", game + 1, record.moves);
+    }
+
+    println!();
+    println!("{}: {wins} wins", first.spec);
+    println!("{}: {losses} wins", second.spec);
+    println!("Draws: {draws}");
+    println!(
+        "Average game length: {:.1} moves",
+        moves as f64 / games as f64
+    );
+
+    let score = (wins as f64 + draws as f64 / 2.0) / games as f64;
+    if score >= 1.0 {
+        println!("Elo difference: unbounded ({} won every game)", first.spec);
+    } else if score <= 0.0 {
+        println!("Elo difference: unbounded ({} won every game)", second.spec);
+    } else {
+        let elo = 400.0 * (score / (1.0 - score)).log10();
+        println!(
+            "Elo difference: {elo:+.0} ({} relative to {})",
+            first.spec, second.spec
+        );
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: tournament [games] [ai] [ai]");
+    eprintln!("  ai: random, easy, medium, hard, perfect or mcts,");
+    eprintln!("      optionally followed by :depth, or :milliseconds for mcts,");
+    eprintln!("      except for random");
+    exit(1)
+}
@@ -0,0 +1,266 @@
+//! The command line of the game, shared by the terminal game and the game on the led matrix.
+//!
+//! Usage: `connect4 [options]`, run with `--help` to list the options.
+use std::{path::Path, process::exit};
+
+use crate::{
+    display::{MatrixDisplay, TerminalDisplay},
+    game::{config::Config, error::GameResult, Game, Rules, SavedGame, TileType},
+    AiLevel,
+};
+
+/// Config read at startup when no other one is given.
+const CONFIG_PATH: &str = "./connect4.conf";
+
+/// Opens the display the game is shown on.
+pub trait Screen {
+    /// Open the display for the board size of the config.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidConfig` if the display can't show a board of this size.
+    fn open(&self, config: &Config) -> GameResult<Box<dyn MatrixDisplay>>;
+}
+
+/// Shows the game in the terminal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalScreen;
+
+impl Screen for TerminalScreen {
+    fn open(&self, _config: &Config) -> GameResult<Box<dyn MatrixDisplay>> {
+        Ok(Box::new(TerminalDisplay::new()))
+    }
+}
+
+/// Options given on the command line.
+#[derive(Debug, Default)]
+struct Args {
+    config: Option<String>,
+    size: Option<(usize, usize)>,
+    connect: Option<usize>,
+    rules: Option<Rules>,
+    no_display: bool,
+    no_buttons: bool,
+    ai: Option<AiLevel>,
+    ai_first: bool,
+    log_level: Option<log::LevelFilter>,
+    load: Option<String>,
+    headless: bool,
+    help: bool,
+}
+
+impl Args {
+    /// Read the options, without the name of the program. Returns [None] if they can't be
+    /// read.
+    fn parse(args: impl IntoIterator<Item = String>) -> Option<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next();
+            match arg.as_str() {
+                "--config" | "-c" => parsed.config = Some(value()?),
+                "--size" => {
+                    let size = value()?;
+                    let (width, height) = size.split_once('x')?;
+                    parsed.size = Some((width.parse().ok()?, height.parse().ok()?));
+                }
+                "--connect" => parsed.connect = Some(value()?.parse().ok()?),
+                "--rules" => parsed.rules = Some(value()?.parse().ok()?),
+                "--no-display" => parsed.no_display = true,
+                "--no-buttons" => parsed.no_buttons = true,
+                "--ai" => parsed.ai = Some(value()?.parse().ok()?),
+                "--ai-first" => parsed.ai_first = true,
+                "--log-level" => parsed.log_level = Some(value()?.parse().ok()?),
+                "--load" => parsed.load = Some(value()?),
+                "--headless" => parsed.headless = true,
+                "--help" | "-h" => parsed.help = true,
+                _ => return None,
+            }
+        }
+        if parsed.ai_first && parsed.ai.is_none() {
+            parsed.ai = Some(AiLevel::default());
+        }
+        Some(parsed)
+    }
+}
+
+/// Run the game with the options given on the command line, showing it on the screen unless
+/// `--no-display` or `--headless` is given.
+///
+/// Exits the process when the options or the config can't be read, or the game can't start.
+///
+/// Run with `--help` to list the options.
+pub fn main(screen: impl Screen) {
+    let args = Args::parse(std::env::args().skip(1)).unwrap_or_else(|| usage());
+    if args.help {
+        print_usage();
+        exit(0);
+    }
+    env_logger::builder()
+        .filter_level(args.log_level.unwrap_or(if args.headless {
+            log::LevelFilter::Warn
+        } else {
+            log::LevelFilter::Debug
+        }))
+        .init();
+
+    let config = match &args.config {
+        Some(path) => Config::from_file(path),
+        None if Path::new(CONFIG_PATH).exists() => Config::from_file(CONFIG_PATH),
+        None => Ok(Config::default()),
+    };
+    let mut config = config.unwrap_or_else(|err| {
+        eprintln!("Could not read the config: {err}");
+        exit(1)
+    });
+    if let Some((width, height)) = args.size {
+        config.width = width;
+        config.height = height;
+    }
+    if let Some(connect) = args.connect {
+        config.connect = connect;
+    }
+    if let Some(rules) = args.rules {
+        config.rules = rules;
+    }
+    // a saved game is continued on the board it was played on
+    if let Some(path) = &args.load {
+        let saved = SavedGame::from_file(path).unwrap_or_else(|err| {
+            eprintln!("Could not load the game: {err}");
+            exit(1)
+        });
+        config.width = saved.width;
+        config.height = saved.height;
+    }
+
+    // start over on the size chosen in the menu, until the players quit
+    let mut load = args.load.clone();
+    loop {
+        match run(&mut config, &args, load.take(), &screen) {
+            Ok(Some((width, height))) => {
+                config.width = width;
+                config.height = height;
+            }
+            Ok(None) => break,
+            Err(err) => {
+                eprintln!("Could not start the game: {err}");
+                exit(1);
+            }
+        }
+    }
+}
+
+/// Set up and play the game on a board of the size of the config.
+///
+/// Returns the board size chosen in the menu, if the players want to play on another board.
+/// The rules and line length chosen in the menu are kept in the config, for the next board.
+fn run(
+    config: &mut Config,
+    args: &Args,
+    load: Option<String>,
+    screen: &impl Screen,
+) -> GameResult<Option<(usize, usize)>> {
+    let mut game = Game::with_config(config.clone())?;
+    if let Err(err) = game.enable_profiles("./profiles.txt") {
+        log::error!("Could not load the player profiles: {err}");
+    }
+
+    if args.headless {
+        game.enable_headless();
+    } else {
+        if !args.no_display {
+            game.set_display(screen.open(config)?);
+        }
+        #[cfg(feature = "gpio")]
+        if !args.no_buttons {
+            game.enable_buttons()?;
+        }
+    }
+
+    if let Some(level) = args.ai {
+        game.enable_ai_with(level);
+        if args.ai_first {
+            game.set_starting_player(TileType::Player2)?;
+        }
+    }
+    if let Some(path) = load {
+        game.load(path)?;
+    }
+
+    game.start();
+    config.connect = game.connect();
+    config.rules = game.rules();
+    Ok(game.requested_size())
+}
+
+fn print_usage() {
+    eprintln!("Usage: connect4 [options]");
+    eprintln!("  -c, --config <file>\tread the settings from the file (default {CONFIG_PATH})");
+    eprintln!("  --size <WxH>\t\tboard size like 7x6, the led matrix shows 6x5, 7x6, 8x7 or 9x7");
+    eprintln!("  --connect <n>\t\tconnect n pieces to win, 4 by default");
+    eprintln!("  --rules <rules>\tplay by the standard or popout rules");
+    eprintln!("  --no-display\t\tdon't use the display, only print the board");
+    eprintln!("  --no-buttons\t\tdon't use the push buttons");
+    eprintln!("  --ai <level>\t\tplay against the ai: random, easy, medium, hard, perfect or mcts");
+    eprintln!("  --ai-first\t\tlet the ai make the first move");
+    eprintln!("  --log-level <level>\toff, error, warn, info, debug or trace");
+    eprintln!("  --load <file>\t\tcontinue a saved game, on the board size it was played on");
+    eprintln!("  --headless\t\tno display or buttons, read every line of stdin in order");
+    eprintln!("  -h, --help\t\tshow this page");
+}
+
+fn usage() -> ! {
+    print_usage();
+    exit(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Option<Args> {
+        Args::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn reads_options() {
+        let args =
+            parse("--size 8x7 --connect 5 --rules popout --ai hard --load game.txt").unwrap();
+        assert_eq!(args.size, Some((8, 7)));
+        assert_eq!(args.connect, Some(5));
+        assert_eq!(args.rules, Some(Rules::PopOut));
+        assert_eq!(args.ai, Some(AiLevel::Hard));
+        assert_eq!(args.load.as_deref(), Some("game.txt"));
+        assert!(!args.ai_first && !args.headless && !args.help);
+
+        let args = parse("-c other.conf --no-display --no-buttons --log-level warn -h").unwrap();
+        assert_eq!(args.config.as_deref(), Some("other.conf"));
+        assert_eq!(args.log_level, Some(log::LevelFilter::Warn));
+        assert!(args.no_display && args.no_buttons && args.help);
+    }
+
+    #[test]
+    fn ai_first_plays_at_default_level() {
+        let args = parse("--ai-first --headless").unwrap();
+        assert_eq!(args.ai, Some(AiLevel::default()));
+        assert!(args.ai_first && args.headless);
+        assert_eq!(
+            parse("--ai easy --ai-first").unwrap().ai,
+            Some(AiLevel::Easy)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_options() {
+        for args in [
+            "--size",
+            "--size 8",
+            "--size 8xseven",
+            "--connect four",
+            "--ai smart",
+            "--fast",
+        ] {
+            assert!(parse(args).is_none(), "{args}");
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::{path::Path, str::FromStr, time::Duration};
+
+use crate::game::error::{Error, GameResult};
+
+/// Color of a single led.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Color {
+    /// The led is turned off.
+    #[default]
+    Off,
+    /// Red.
+    Red,
+    /// Green.
+    Green,
+    /// Blue.
+    Blue,
+    /// Yellow.
+    Yellow,
+    /// Cyan.
+    Cyan,
+    /// Magenta.
+    Magenta,
+    /// White.
+    White,
+}
+
+/// Blinking pattern of a led.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blink {
+    /// How long the led is on every interval.
+    pub dur: Duration,
+    /// Time between the start of two blinks.
+    pub int: Duration,
+}
+
+/// State of a single led.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Led {
+    /// Color of the led.
+    pub color: Color,
+    /// Blinking pattern, [None] if the led is on continuously.
+    pub blink: Option<Blink>,
+}
+
+/// A single frame of an animation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnimationFrame {
+    /// How long the frame is shown.
+    pub dur: Duration,
+    /// Leds lit by the frame, as (x, y, led).
+    pub leds: Vec<(usize, usize, Led)>,
+    /// If the leds of the previous frames are cleared before showing this frame.
+    pub reset: bool,
+}
+
+/// An animation drawn on top of the frame shown by a display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Animation {
+    /// Frames of the animation, in order.
+    pub frames: Vec<AnimationFrame>,
+    /// If the animation starts over after the last frame.
+    pub looping: bool,
+    /// Number of times a looping animation plays, 0 meaning until the animations are cleared.
+    pub repeats: usize,
+    /// If the last frame stays visible once the animation is done.
+    pub keep_last: bool,
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" | "none" => Ok(Color::Off),
+            "red" => Ok(Color::Red),
+            "green" => Ok(Color::Green),
+            "blue" => Ok(Color::Blue),
+            "yellow" => Ok(Color::Yellow),
+            "cyan" => Ok(Color::Cyan),
+            "magenta" => Ok(Color::Magenta),
+            "white" => Ok(Color::White),
+            _ => Err(Error::InvalidInput(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Color::Off => "off",
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Blue => "blue",
+            Color::Yellow => "yellow",
+            Color::Cyan => "cyan",
+            Color::Magenta => "magenta",
+            Color::White => "white",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Led {
+    /// Create a led that is on continuously.
+    pub fn with_color(color: Color) -> Self {
+        Self { color, blink: None }
+    }
+
+    /// Get the color shown `elapsed` after the led was turned on.
+    ///
+    /// Blinking leds are off while outside the on part of their blink.
+    pub(crate) fn color_at(&self, elapsed: Duration) -> Color {
+        match self.blink {
+            Some(blink) if !blink.int.is_zero() => {
+                if elapsed.as_nanos() % blink.int.as_nanos() < blink.dur.as_nanos() {
+                    self.color
+                } else {
+                    Color::Off
+                }
+            }
+            _ => self.color,
+        }
+    }
+}
+
+impl AnimationFrame {
+    /// Create a new animation frame.
+    pub fn new(dur: Duration, leds: Vec<(usize, usize, Led)>, reset: bool) -> Self {
+        Self { dur, leds, reset }
+    }
+}
+
+impl Animation {
+    /// Create a new animation.
+    pub fn new(
+        looping: bool,
+        frames: Vec<AnimationFrame>,
+        repeats: usize,
+        keep_last: bool,
+    ) -> Self {
+        Self {
+            frames,
+            looping,
+            repeats,
+            keep_last,
+        }
+    }
+
+    /// Load an animation from a `.mtxani` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file can't be read.
+    ///
+    /// Returns `Error::InvalidAnimation` if the file is not a valid animation.
+    pub fn from_file(path: impl AsRef<Path>) -> GameResult<Self> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Total duration of a single play of the animation.
+    pub(crate) fn dur(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.dur).sum()
+    }
+
+    /// Get the leds that are lit `elapsed` after the animation started.
+    ///
+    /// Returns [None] once the animation is done and nothing is left to show.
+    pub(crate) fn leds_at(&self, elapsed: Duration) -> Option<Vec<(usize, usize, Led)>> {
+        let total = self.dur();
+        if total.is_zero() {
+            return None;
+        }
+
+        let plays = (elapsed.as_nanos() / total.as_nanos()) as usize;
+        let done = match (self.looping, self.repeats) {
+            (true, 0) => false,
+            (true, repeats) => plays >= repeats,
+            (false, _) => plays >= 1,
+        };
+
+        // index of the frame that is currently shown
+        let current = if done {
+            if !self.keep_last {
+                return None;
+            }
+            self.frames.len() - 1
+        } else {
+            let mut offset = elapsed.as_nanos() % total.as_nanos();
+            let mut current = 0;
+            for (i, frame) in self.frames.iter().enumerate() {
+                current = i;
+                if offset < frame.dur.as_nanos() {
+                    break;
+                }
+                offset -= frame.dur.as_nanos();
+            }
+            current
+        };
+
+        // frames after the last reset add to each other
+        let first = (0..=current)
+            .rev()
+            .find(|&i| self.frames[i].reset)
+            .unwrap_or(0);
+        Some(
+            self.frames[first..=current]
+                .iter()
+                .flat_map(|frame| frame.leds.iter().copied())
+                .collect(),
+        )
+    }
+}
+
+impl FromStr for Animation {
+    type Err = Error;
+
+    /// Parse an animation in the `.mtxani` format.
+    ///
+    /// The file starts with `animation`, followed by the `loop`, `repeats` and `keep_last`
+    /// settings. Every frame starts with `frame`, followed by its `dur` in milliseconds, `rst`,
+    /// and an `x y color` line per lit led.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            |line: usize, msg: &str| Error::InvalidAnimation(format!("line {line}: {msg}"));
+
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        match lines.next() {
+            Some((_, "animation")) => (),
+            Some((line, _)) => return Err(invalid(line, "expected `animation`")),
+            None => return Err(Error::InvalidAnimation(String::from("file is empty"))),
+        }
+
+        let mut animation = Animation::new(false, Vec::new(), 0, false);
+        for (line, text) in lines {
+            let words = text.split_whitespace().collect::<Vec<&str>>();
+            let frame = animation.frames.last_mut();
+            match (words.as_slice(), frame) {
+                (["frame"], _) => {
+                    animation
+                        .frames
+                        .push(AnimationFrame::new(Duration::ZERO, Vec::new(), false))
+                }
+                (["loop", value], None) => {
+                    animation.looping = value
+                        .parse()
+                        .map_err(|_| invalid(line, "expected a bool"))?
+                }
+                (["repeats", value], None) => {
+                    animation.repeats = value
+                        .parse()
+                        .map_err(|_| invalid(line, "expected a number"))?
+                }
+                (["keep_last", value], None) => {
+                    animation.keep_last = value
+                        .parse()
+                        .map_err(|_| invalid(line, "expected a bool"))?
+                }
+                (["dur", value], Some(frame)) => {
+                    let ms = value
+                        .parse()
+                        .map_err(|_| invalid(line, "expected a number"))?;
+                    frame.dur = Duration::from_millis(ms);
+                }
+                (["rst", value], Some(frame)) => {
+                    frame.reset = value
+                        .parse()
+                        .map_err(|_| invalid(line, "expected a bool"))?
+                }
+                ([x, y, color], Some(frame)) => {
+                    let x = x.parse().map_err(|_| invalid(line, "expected a number"))?;
+                    let y = y.parse().map_err(|_| invalid(line, "expected a number"))?;
+                    let color = color.parse().map_err(|_| invalid(line, "unknown color"))?;
+                    frame.leds.push((x, y, Led::with_color(color)));
+                }
+                _ => return Err(invalid(line, &format!("unexpected `{text}`"))),
+            }
+        }
+        Ok(animation)
+    }
+}
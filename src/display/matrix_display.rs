@@ -0,0 +1,29 @@
+use crate::{
+    display::animation::{Animation, Led},
+    game::error::GameResult,
+};
+
+/// Something the board can be shown on, like the led matrix or the terminal.
+///
+/// The display shows a frame of leds, row 0 being the status bar above the board. Animations
+/// are drawn on top of the frame until they finish or are cleared.
+pub trait MatrixDisplay {
+    /// Show a new frame, indexed as `frame[y][x]`.
+    fn sync(&mut self, frame: Vec<Vec<Led>>) -> GameResult<()>;
+
+    /// Start playing an animation on top of the frame.
+    fn add_animation(&mut self, animation: Animation) -> GameResult<()>;
+
+    /// Stop all animations.
+    fn clear_animations(&mut self);
+
+    /// Shut the display down. Called when the display is no longer used.
+    fn stop(&mut self) -> GameResult<()> {
+        Ok(())
+    }
+
+    /// If the display is drawn in the terminal, making printing the board as text redundant.
+    fn in_terminal(&self) -> bool {
+        false
+    }
+}
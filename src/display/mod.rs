@@ -0,0 +1,9 @@
+mod animation;
+mod matrix_display;
+mod recording;
+mod terminal;
+
+pub use animation::{Animation, AnimationFrame, Blink, Color, Led};
+pub use matrix_display::MatrixDisplay;
+pub use recording::{DisplayEvent, RecordingDisplay};
+pub use terminal::TerminalDisplay;
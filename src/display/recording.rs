@@ -0,0 +1,104 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    display::{
+        animation::{Animation, Led},
+        matrix_display::MatrixDisplay,
+    },
+    game::error::GameResult,
+};
+
+/// Something that was done to a [`RecordingDisplay`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisplayEvent {
+    /// A frame was synced, indexed as `frame[y][x]`.
+    Sync(Vec<Vec<Led>>),
+    /// An animation was added.
+    AddAnimation(Animation),
+    /// The animations were cleared.
+    ClearAnimations,
+    /// The display was stopped.
+    Stop,
+}
+
+/// Display that shows nothing, but records everything it is asked to show.
+///
+/// Clones share the same recording, so a clone can be kept to inspect what a game pushed to
+/// the display.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingDisplay {
+    events: Arc<Mutex<Vec<DisplayEvent>>>,
+}
+
+impl RecordingDisplay {
+    /// Create a new display with an empty recording.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get everything done to the display so far, in order.
+    pub fn events(&self) -> Vec<DisplayEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Get all synced frames, in order.
+    pub fn frames(&self) -> Vec<Vec<Vec<Led>>> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match event {
+                DisplayEvent::Sync(frame) => Some(frame.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get the last synced frame.
+    pub fn last_frame(&self) -> Option<Vec<Vec<Led>>> {
+        self.frames().pop()
+    }
+
+    /// Get all added animations, in order.
+    pub fn animations(&self) -> Vec<Animation> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match event {
+                DisplayEvent::AddAnimation(animation) => Some(animation.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Throw away the recording.
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    fn record(&self, event: DisplayEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+impl MatrixDisplay for RecordingDisplay {
+    fn sync(&mut self, frame: Vec<Vec<Led>>) -> GameResult<()> {
+        self.record(DisplayEvent::Sync(frame));
+        Ok(())
+    }
+
+    fn add_animation(&mut self, animation: Animation) -> GameResult<()> {
+        self.record(DisplayEvent::AddAnimation(animation));
+        Ok(())
+    }
+
+    fn clear_animations(&mut self) {
+        self.record(DisplayEvent::ClearAnimations);
+    }
+
+    fn stop(&mut self) -> GameResult<()> {
+        self.record(DisplayEvent::Stop);
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    display::{
+        animation::{Animation, Color, Led},
+        matrix_display::MatrixDisplay,
+    },
+    game::error::GameResult,
+};
+
+/// Time between two redraws.
+const REFRESH: Duration = Duration::from_millis(20);
+
+/// Shows the board in a terminal using ANSI escape codes.
+///
+/// The board stays at the top of the terminal and is redrawn in place, while all other output
+/// scrolls below it. Row 0 is shown above the board, so the selected column is visible.
+pub struct TerminalDisplay {
+    state: Arc<Mutex<State>>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// What is shown on the terminal, shared with the render thread.
+struct State {
+    frame: Vec<Vec<Led>>,
+    /// Animations being played, with the time they were added.
+    animations: Vec<(Animation, Instant)>,
+    /// Time the display started, used to blink the leds of the frame.
+    start: Instant,
+}
+
+impl TerminalDisplay {
+    /// Start drawing to the terminal.
+    ///
+    /// Nothing is drawn until the first frame is synced.
+    pub fn new() -> Self {
+        let state = Arc::new(Mutex::new(State {
+            frame: Vec::new(),
+            animations: Vec::new(),
+            start: Instant::now(),
+        }));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread = {
+            let state = Arc::clone(&state);
+            let running = Arc::clone(&running);
+            std::thread::spawn(move || {
+                let mut last = String::new();
+                while running.load(Ordering::Relaxed) {
+                    let colors = state.lock().unwrap().colors(Instant::now());
+                    if !colors.is_empty() {
+                        let drawn = draw(&colors);
+                        if drawn != last {
+                            let mut out = std::io::stdout().lock();
+                            // save the cursor, so output below the board continues where it was
+                            drop(write!(out, "\x1b7{drawn}\x1b8"));
+                            drop(out.flush());
+                            last = drawn;
+                        }
+                    }
+                    std::thread::sleep(REFRESH);
+                }
+            })
+        };
+
+        Self {
+            state,
+            running,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Default for TerminalDisplay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MatrixDisplay for TerminalDisplay {
+    fn sync(&mut self, frame: Vec<Vec<Led>>) -> GameResult<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.frame.len() != frame.len() {
+            // reserve the top of the terminal for the board and scroll the output below it
+            let top = frame.len() + 4;
+            let mut out = std::io::stdout().lock();
+            write!(out, "\x1b[2J\x1b[{top}r\x1b[{top};1H")?;
+            out.flush()?;
+        }
+        state.frame = frame;
+        Ok(())
+    }
+
+    fn add_animation(&mut self, animation: Animation) -> GameResult<()> {
+        self.state
+            .lock()
+            .unwrap()
+            .animations
+            .push((animation, Instant::now()));
+        Ok(())
+    }
+
+    fn clear_animations(&mut self) {
+        self.state.lock().unwrap().animations.clear();
+    }
+
+    fn stop(&mut self) -> GameResult<()> {
+        if let Some(thread) = self.thread.take() {
+            self.running.store(false, Ordering::Relaxed);
+            drop(thread.join()); // drop to discard result without warning
+
+            if !self.state.lock().unwrap().frame.is_empty() {
+                // give the whole terminal back to the output
+                let mut out = std::io::stdout().lock();
+                write!(out, "\x1b[r\x1b[999;1H")?;
+                out.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn in_terminal(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for TerminalDisplay {
+    fn drop(&mut self) {
+        drop(self.stop()); // drop to discard result without warning
+    }
+}
+
+impl State {
+    /// Get the colors shown at the given time, indexed as `colors[y][x]`.
+    ///
+    /// Animations that are done are removed.
+    fn colors(&mut self, now: Instant) -> Vec<Vec<Color>> {
+        let mut colors = self
+            .frame
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|led| led.color_at(now - self.start))
+                    .collect::<Vec<Color>>()
+            })
+            .collect::<Vec<Vec<Color>>>();
+
+        let frame = &mut colors;
+        self.animations.retain(|(animation, added)| {
+            let elapsed = now - *added;
+            let leds = match animation.leds_at(elapsed) {
+                Some(leds) => leds,
+                None => return false,
+            };
+            for (x, y, led) in leds {
+                if let Some(color) = frame.get_mut(y).and_then(|row| row.get_mut(x)) {
+                    *color = led.color_at(elapsed);
+                }
+            }
+            true
+        });
+        colors
+    }
+}
+
+/// Draw the colors, starting at the top left of the terminal.
+fn draw(colors: &[Vec<Color>]) -> String {
+    let width = colors[0].len();
+    let mut drawn = String::new();
+
+    for (y, row) in colors.iter().enumerate() {
+        let (left, right) = if y == 0 { (" ", "  ") } else { ("│", " │") };
+        drawn.push_str(&format!("\x1b[{};1H\x1b[2K{left}", y + 1));
+        for &color in row {
+            drawn.push(' ');
+            drawn.push_str(&cell(color, y == 0));
+        }
+        drawn.push_str(right);
+    }
+
+    drawn.push_str(&format!(
+        "\x1b[{};1H\x1b[2K└{}─┘",
+        colors.len() + 1,
+        "──".repeat(width)
+    ));
+    drawn.push_str(&format!("\x1b[{};1H\x1b[2K ", colors.len() + 2));
+    for x in 1..=width {
+        drawn.push_str(&format!(" {x}"));
+    }
+    drawn
+}
+
+/// Draw a single led. Empty spots on the board are drawn as dots.
+fn cell(color: Color, status_bar: bool) -> String {
+    let code = match color {
+        Color::Off if status_bar => return String::from(" "),
+        Color::Off => return String::from("\x1b[90m·\x1b[0m"),
+        Color::Red => 91,
+        Color::Green => 92,
+        Color::Yellow => 93,
+        Color::Blue => 94,
+        Color::Magenta => 95,
+        Color::Cyan => 96,
+        Color::White => 97,
+    };
+    format!("\x1b[{code}m●\x1b[0m")
+}
@@ -0,0 +1,75 @@
+//! Access to the boards for the benchmarks in `benches/`, not part of the api.
+use super::{bitboard::BitBoard, board::Board, components::CheckWin, TileType};
+
+/// A position on the standard board, stored as an array of tiles, as tiles with their moves
+/// and as a bitboard.
+#[derive(Debug, Clone)]
+pub struct Position {
+    array: [[TileType; 7]; 7],
+    tiles: Board,
+    bits: BitBoard,
+}
+
+impl Position {
+    /// Play the columns in order, 1 indexed, starting with player 1. Stops at the first column
+    /// that is full or after a player wins.
+    pub fn play(cols: &[usize]) -> Self {
+        let mut tiles = Board::new(7, 7).expect("standard board");
+        let mut bits = BitBoard::new(7, 7).expect("standard board fits");
+        let mut tile = TileType::Player1;
+        for &col in cols {
+            if tiles.place(col, tile).is_err() || bits.place(col, tile).is_err() {
+                break;
+            }
+            if !matches!(bits.check_win(), CheckWin::None) {
+                break;
+            }
+            tile = tile.opponent();
+        }
+        let mut array = [[TileType::Empty; 7]; 7];
+        for (y, row) in array.iter_mut().enumerate().skip(1) {
+            for (x, spot) in row.iter_mut().enumerate() {
+                *spot = tiles.get(x, y).expect("on the board");
+            }
+        }
+        Self { array, tiles, bits }
+    }
+
+    /// Check for a win by scanning every spot of the array in four directions, the way the
+    /// board checked for four in a row before it had a bitboard.
+    pub fn array_win(&self) -> bool {
+        let board = &self.array;
+        for y in 1..7 {
+            for x in 0..7 {
+                let tile = board[y][x];
+                if tile == TileType::Empty {
+                    continue;
+                }
+                // check right
+                if x < 4 && (1..4).all(|i| board[y][x + i] == tile) {
+                    return true;
+                }
+                if y > 3 {
+                    // check up, up & right and up & left
+                    if (1..4).all(|i| board[y - i][x] == tile)
+                        || x < 4 && (1..4).all(|i| board[y - i][x + i] == tile)
+                        || x > 2 && (1..4).all(|i| board[y - i][x - i] == tile)
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Check the last move for a win by scanning the tiles around it.
+    pub fn scan_win(&self) -> bool {
+        matches!(self.tiles.check_win(), CheckWin::Player(_))
+    }
+
+    /// Check the last move for a win with the bitboard.
+    pub fn bit_win(&self) -> bool {
+        matches!(self.bits.check_win(), CheckWin::Player(_))
+    }
+}
@@ -0,0 +1,342 @@
+use super::{
+    board::Board,
+    components::{CheckWin, Line, TileType, DEFAULT_CONNECT},
+    error::{Error, GameResult},
+};
+
+/// Board position stored as two bitmasks, one per player.
+///
+/// Every column takes up `height` bits, the lowest bit being the bottom row. Row 0 of
+/// [`Board`] is used as a status bar, so a column only holds `height - 1` tiles and the top bit
+/// stays empty. This empty bit keeps lines from wrapping around to the next column when
+/// shifting.
+///
+/// Only boards with `width * height <= 64` fit, see [`BitBoard::fits`]. The ai only drops
+/// tiles when searching, so pops are left to [`Board`].
+#[derive(Debug, Clone)]
+pub(crate) struct BitBoard {
+    width: usize,
+    height: usize,
+    player1: u64,
+    player2: u64,
+    /// Number of tiles a player has to connect to win.
+    connect: usize,
+    /// Columns of the moves played so far, zero indexed.
+    moves: Vec<usize>,
+}
+
+impl BitBoard {
+    /// Check if a board of the given size fits in a bitboard. The height includes the status
+    /// bar.
+    pub(crate) const fn fits(width: usize, height: usize) -> bool {
+        width * height <= 64
+    }
+
+    /// Create a new empty BitBoard. The height includes the status bar.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidDim` if the board is smaller than 4x4, or too large to fit.
+    pub(crate) fn new(width: usize, height: usize) -> GameResult<Self> {
+        if width < 4 || height < 5 || !Self::fits(width, height) {
+            return Err(Error::InvalidDim);
+        }
+        Ok(Self {
+            width,
+            height,
+            player1: 0,
+            player2: 0,
+            connect: DEFAULT_CONNECT,
+            moves: Vec::new(),
+        })
+    }
+
+    /// Create a BitBoard holding the tiles of the given board, without any moves played.
+    /// Returns [None] if the board doesn't fit.
+    pub(crate) fn from_board(board: &Board) -> Option<Self> {
+        let mut bits = Self::new(board.width(), board.height()).ok()?;
+        for x in 0..bits.width {
+            for y in 1..bits.height {
+                let bit = bits.bit(x, y);
+                match board.get(x, y) {
+                    Ok(TileType::Player1) => bits.player1 |= bit,
+                    Ok(TileType::Player2) => bits.player2 |= bit,
+                    _ => (),
+                }
+            }
+        }
+        bits.connect = board.connect();
+        Some(bits)
+    }
+
+    /// Get the number of tiles a player has to connect to win.
+    pub(crate) fn connect(&self) -> usize {
+        self.connect
+    }
+
+    /// Get the number of columns.
+    pub(crate) fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Place a tile in the given column. The column is one indexed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidColumn` if the column is outside the board dimensions.
+    ///
+    /// Returns `Error::InvalidType` if the tile is of type `TileType::Empty`.
+    ///
+    /// Returns `Error::ColumnFull` if the column has no empty spots left.
+    pub(crate) fn place(&mut self, col: usize, tile: TileType) -> GameResult<()> {
+        if col < 1 || col > self.width {
+            return Err(Error::InvalidColumn);
+        }
+        let x = col - 1;
+        let bit = (self.mask() + self.bottom(x)) & self.column(x);
+        if bit == 0 {
+            return Err(Error::ColumnFull);
+        }
+        match tile {
+            TileType::Player1 => self.player1 |= bit,
+            TileType::Player2 => self.player2 |= bit,
+            TileType::Empty => return Err(Error::InvalidType),
+        }
+        self.moves.push(x);
+        Ok(())
+    }
+
+    /// Get the row of the first empty spot in the column. The column is zero indexed.
+    pub(crate) fn first_empty(&self, col: usize) -> GameResult<usize> {
+        if col >= self.width {
+            return Err(Error::ColumnFull);
+        }
+        let bit = (self.mask() + self.bottom(col)) & self.column(col);
+        if bit == 0 {
+            return Err(Error::ColumnFull);
+        }
+        Ok(self.coords(bit.trailing_zeros() as usize).0)
+    }
+
+    /// Check if the last move connected enough tiles to win.
+    ///
+    /// Returns every winning line through the tile placed last, or if there is a draw. A board
+    /// without moves is checked as a whole.
+    pub(crate) fn check_win(&self) -> CheckWin {
+        let last = self
+            .moves
+            .last()
+            .map(|&x| Self::top(self.mask() & self.column(x)));
+        let mut changed = last.unwrap_or_else(|| self.mask());
+        let full = self.mask() == self.full();
+        let last = last.map(|top| match self.player1 & top {
+            0 => TileType::Player2,
+            _ => TileType::Player1,
+        });
+
+        // only look for lines in the directions a player connected enough tiles in
+        let connected = [self.player1, self.player2]
+            .map(|tiles| self.directions().map(|shift| self.connected(tiles, shift)));
+        let mut lines: [Vec<Line>; 2] = Default::default();
+        if !connected.iter().flatten().any(|&connected| connected) {
+            return CheckWin::decide(lines, last, full);
+        }
+        while changed != 0 {
+            let bit = changed.trailing_zeros() as usize;
+            changed &= changed - 1;
+            let (player, tiles) = match self.player1 >> bit & 1 {
+                1 => (0, self.player1),
+                _ => (1, self.player2),
+            };
+            for (shift, _) in self
+                .directions()
+                .into_iter()
+                .zip(connected[player])
+                .filter(|&(_, connected)| connected)
+            {
+                let line = self.line_through(tiles, bit, shift);
+                if line.len() >= self.connect && !lines[player].contains(&line) {
+                    lines[player].push(line);
+                }
+            }
+        }
+        CheckWin::decide(lines, last, full)
+    }
+
+    /// Check if the tiles connect enough in steps of `shift` anywhere, without finding the
+    /// lines.
+    ///
+    /// Keeps the bits that start a line by shifting the tiles onto themselves once for every
+    /// further tile of the line.
+    fn connected(&self, tiles: u64, shift: usize) -> bool {
+        let mut starts = tiles;
+        for i in 1..self.connect {
+            starts &= tiles.checked_shr((i * shift) as u32).unwrap_or(0);
+        }
+        starts != 0
+    }
+
+    /// Get the connected bits through the given bit of a player, going in steps of `shift`.
+    ///
+    /// The empty top bit of every column keeps lines from wrapping around.
+    fn line_through(&self, tiles: u64, bit: usize, shift: usize) -> Line {
+        let set = |bit: usize| bit < 64 && tiles >> bit & 1 == 1;
+        let mut start = bit;
+        while start >= shift && set(start - shift) {
+            start -= shift;
+        }
+        (start..)
+            .step_by(shift)
+            .take_while(|&bit| set(bit))
+            .map(|bit| self.coords(bit))
+            .collect()
+    }
+
+    /// Get the type of tile at position (x, y).
+    pub(crate) fn get(&self, x: usize, y: usize) -> GameResult<TileType> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::InvalidDim);
+        }
+        if y == 0 {
+            return Ok(TileType::Empty);
+        }
+
+        let bit = self.bit(x, y);
+        if self.player1 & bit != 0 {
+            Ok(TileType::Player1)
+        } else if self.player2 & bit != 0 {
+            Ok(TileType::Player2)
+        } else {
+            Ok(TileType::Empty)
+        }
+    }
+
+    /// Undo the last move.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::NoUndos` if no moves have been made.
+    pub(crate) fn undo_last(&mut self) -> GameResult<()> {
+        let x = self.moves.pop().ok_or(Error::NoUndos)?;
+        let top = Self::top(self.mask() & self.column(x));
+        self.player1 &= !top;
+        self.player2 &= !top;
+        Ok(())
+    }
+
+    /// All occupied spots.
+    fn mask(&self) -> u64 {
+        self.player1 | self.player2
+    }
+
+    /// The highest set bit of a column, which is the tile on top.
+    fn top(column: u64) -> u64 {
+        1 << (63 - column.leading_zeros())
+    }
+
+    /// Shift amounts for the vertical, horizontal and both diagonal directions.
+    fn directions(&self) -> [usize; 4] {
+        [1, self.height, self.height + 1, self.height - 1]
+    }
+
+    /// The spot at position (x, y).
+    fn bit(&self, x: usize, y: usize) -> u64 {
+        1 << (x * self.height + self.height - 1 - y)
+    }
+
+    /// The bottom spot of a column.
+    fn bottom(&self, x: usize) -> u64 {
+        1 << (x * self.height)
+    }
+
+    /// All playable spots of a column.
+    fn column(&self, x: usize) -> u64 {
+        ((1 << (self.height - 1)) - 1) << (x * self.height)
+    }
+
+    /// All playable spots on the board.
+    fn full(&self) -> u64 {
+        (0..self.width).fold(0, |full, x| full | self.column(x))
+    }
+
+    /// Convert a bit index to a (y, x) position.
+    fn coords(&self, bit: usize) -> (usize, usize) {
+        (self.height - 1 - bit % self.height, bit / self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    /// Describe a result with its lines sorted, the boards may find them in a different order.
+    fn sorted(win: CheckWin) -> String {
+        match win {
+            CheckWin::Player(mut lines) => {
+                lines.iter_mut().for_each(|line| line.sort_unstable());
+                lines.sort_unstable();
+                format!("{lines:?}")
+            }
+            CheckWin::Draw => "draw".to_string(),
+            CheckWin::None => "none".to_string(),
+        }
+    }
+
+    /// Play random drops and undos on both boards until the game is decided, checking
+    /// after every move that they hold the same tiles and find the same lines.
+    fn agree(rng: &mut StdRng, (width, height): (usize, usize), connect: usize) {
+        let mut tiles = Board::new(width, height).unwrap();
+        tiles.set_connect(connect).unwrap();
+        let mut bits = BitBoard::from_board(&tiles).unwrap();
+
+        let mut tile = TileType::Player1;
+        loop {
+            let col = rng.gen_range(1..=width);
+            let (a, b) = match rng.gen_range(0..10) {
+                0 => (tiles.undo_last(), bits.undo_last()),
+                _ => (tiles.place(col, tile), bits.place(col, tile)),
+            };
+            assert_eq!(
+                a.is_ok(),
+                b.is_ok(),
+                "{width}x{height} column {col}\n{tiles}"
+            );
+            if a.is_err() {
+                continue;
+            }
+            tile = tile.opponent();
+
+            for x in 0..width {
+                for y in 1..height {
+                    assert_eq!(tiles.get(x, y).unwrap(), bits.get(x, y).unwrap());
+                }
+                assert_eq!(tiles.first_empty(x).ok(), bits.first_empty(x).ok());
+            }
+            let win = sorted(tiles.check_win());
+            assert_eq!(win, sorted(bits.check_win()), "{width}x{height}\n{tiles}");
+            if win != "none" {
+                return;
+            }
+        }
+    }
+
+    #[test]
+    fn agrees_with_board() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..500 {
+            for connect in 3..=5 {
+                for size in [(6, 6), (7, 7), (8, 8), (9, 7)] {
+                    agree(&mut rng, size, connect);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn board_too_large() {
+        assert!(BitBoard::new(9, 8).is_err());
+        assert!(BitBoard::new(8, 8).is_ok());
+    }
+}
@@ -1,33 +1,95 @@
+use std::str::FromStr;
+
 use super::{
-    components::{Check4, TileType},
+    components::{CheckWin, Line, Played, TileType, DEFAULT_CONNECT, MIN_CONNECT},
     error::{Error, GameResult},
 };
 
+/// Directions of lines as (dx, dy): horizontal, vertical and both diagonals.
+const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+/// A board with its dimensions chosen at runtime.
+///
+/// Row 0 is the status bar showing the selected column, so a board of height 7 has 6
+/// playable rows. The game and the ai run on any size that fits the display; the menu offers
+/// the sizes in [`BOARD_SIZES`](super::config::BOARD_SIZES).
+///
+/// The ai doesn't search on this board but on a [`SearchBoard`], which uses a bitboard when
+/// the board fits in one.
+///
+/// [`SearchBoard`]: super::search_board::SearchBoard
 #[derive(Debug, Clone)]
-pub(crate) struct Board<const W: usize, const H: usize> {
-    board: [[TileType; W]; H],
-    last_move: Option<(usize, usize, TileType)>, // x, y, previous tile // TODO move history vec
+pub(crate) struct Board {
+    width: usize,
+    height: usize,
+    /// Number of tiles a player has to connect to win.
+    connect: usize,
+    /// Tiles stored row by row, starting with the status bar.
+    tiles: Vec<TileType>,
+    /// Moves played so far, in order.
+    history: Vec<Played>,
+    /// Moves that have been undone and can be redone, most recent last.
+    redo: Vec<Played>,
+    /// Player on move before the first move of the history.
+    start: TileType,
     selected: usize,
     active_type: TileType,
 }
 
-impl<const W: usize, const H: usize> Board<W, H> {
-    /// Create a new Board.
-    pub(crate) fn new() -> GameResult<Self> {
-        if W < 4 || H < 5 {
+impl Board {
+    /// Create a new board of the given size. The height includes the status bar.
+    ///
+    /// Players have to connect [`DEFAULT_CONNECT`] tiles to win, see [`Board::set_connect`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidDim` if the board is smaller than 4x4.
+    pub(crate) fn new(width: usize, height: usize) -> GameResult<Self> {
+        if width < 4 || height < 5 {
             return Err(Error::InvalidDim);
         }
-        let mut board = [[TileType::default(); W]; H];
-        board[0][0] = TileType::Player1;
+        let mut tiles = vec![TileType::default(); width * height];
+        tiles[0] = TileType::Player1;
         Ok(Self {
-            board,
-            last_move: None,
+            width,
+            height,
+            connect: DEFAULT_CONNECT,
+            tiles,
+            history: Vec::new(),
+            redo: Vec::new(),
+            start: TileType::Player1,
             selected: 0,
             active_type: TileType::Empty,
         })
     }
 
-    /// Place a tile in the given column. The column is zero indexed.
+    /// Get the number of tiles a player has to connect to win.
+    pub(crate) fn connect(&self) -> usize {
+        self.connect
+    }
+
+    /// Set the number of tiles a player has to connect to win.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidConnect` if the line is too short, or doesn't fit on the board.
+    pub(crate) fn set_connect(&mut self, connect: usize) -> GameResult<()> {
+        if connect < MIN_CONNECT || connect > self.width.max(self.height - 1) {
+            return Err(Error::InvalidConnect(connect));
+        }
+        self.connect = connect;
+        Ok(())
+    }
+
+    fn tile(&self, x: usize, y: usize) -> TileType {
+        self.tiles[y * self.width + x]
+    }
+
+    fn tile_mut(&mut self, x: usize, y: usize) -> &mut TileType {
+        &mut self.tiles[y * self.width + x]
+    }
+
+    /// Place a tile in the given column. The column is 1 indexed.
     ///
     /// # Errors
     ///
@@ -36,162 +98,298 @@ impl<const W: usize, const H: usize> Board<W, H> {
     /// Returns `Error::InvalidType` if the tile is of type `TileType::Empty`.
     ///
     /// Returns `Error::ColumnFull` if it failed to place the tile anywhere in the column.
+    ///
+    /// A successful placement clears the redo history.
     pub(crate) fn place(&mut self, col: usize, tile: TileType) -> GameResult<()> {
-        if col < 1 || col > W {
+        self.drop_tile(col, tile)?;
+        self.redo.clear();
+        Ok(())
+    }
+
+    /// Place a tile without touching the redo history.
+    fn drop_tile(&mut self, col: usize, tile: TileType) -> GameResult<()> {
+        if col < 1 || col > self.width {
             return Err(Error::InvalidColumn);
         } else if tile == TileType::Empty {
             return Err(Error::InvalidType);
         }
-        for y in (1..H).rev() {
-            if self.board[y][col - 1] == TileType::Empty {
-                self.last_move = Some((col - 1, y, self.board[y][col - 1]));
-                self.board[y][col - 1] = tile;
-                return Ok(());
-            }
+        let y = self.first_empty(col - 1)?;
+        self.history.push(Played {
+            x: col - 1,
+            y,
+            tile,
+            pop: false,
+        });
+        *self.tile_mut(col - 1, y) = tile;
+        Ok(())
+    }
+
+    /// Pop the bottom tile out of the given column, making the tiles above it fall down. The
+    /// column is 1 indexed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidColumn` if the column is outside the board dimensions.
+    ///
+    /// Returns `Error::InvalidType` if the tile is of type `TileType::Empty`.
+    ///
+    /// Returns `Error::CantPop` if the bottom tile of the column is not of the given type.
+    ///
+    /// A successful pop clears the redo history.
+    pub(crate) fn pop(&mut self, col: usize, tile: TileType) -> GameResult<()> {
+        self.pop_tile(col, tile)?;
+        self.redo.clear();
+        Ok(())
+    }
+
+    /// Pop a tile without touching the redo history.
+    fn pop_tile(&mut self, col: usize, tile: TileType) -> GameResult<()> {
+        if col < 1 || col > self.width {
+            return Err(Error::InvalidColumn);
+        } else if tile == TileType::Empty {
+            return Err(Error::InvalidType);
         }
-        Err(Error::ColumnFull)
+        let (x, bottom) = (col - 1, self.height - 1);
+        if self.tile(x, bottom) != tile {
+            return Err(Error::CantPop);
+        }
+        for y in (2..=bottom).rev() {
+            *self.tile_mut(x, y) = self.tile(x, y - 1);
+        }
+        *self.tile_mut(x, 1) = TileType::Empty;
+        self.history.push(Played {
+            x,
+            y: bottom,
+            tile,
+            pop: true,
+        });
+        Ok(())
     }
 
+    /// Get the lowest empty row of the column. The column is zero indexed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::ColumnFull` if the column has no empty spots left.
     pub(crate) fn first_empty(&self, col: usize) -> GameResult<usize> {
-        for y in (1..H).rev() {
-            if self.board[y][col] == TileType::Empty {
-                return Ok(y);
-            }
-        }
-        Err(Error::ColumnFull)
+        (1..self.height)
+            .rev()
+            .find(|&y| self.tile(col, y) == TileType::Empty)
+            .ok_or(Error::ColumnFull)
     }
 
-    /// Check if any player has 4 connected tiles.
+    /// Check if the last move connected enough tiles to win.
     ///
-    /// Returns if a player won or if there is a draw.
-    pub(crate) fn check4(&self) -> Check4 {
-        let mut draw = true;
-        for y in 1..H {
-            for x in 0..W {
-                let tile = self.board[y][x];
-                if tile == TileType::Empty {
-                    draw = false;
-                } else {
-                    // check right
-                    if x < W - 3
-                        && self.board[y][x + 1] == tile
-                        && self.board[y][x + 2] == tile
-                        && self.board[y][x + 3] == tile
-                    {
-                        return Check4::Player([(y, x), (y, x + 1), (y, x + 2), (y, x + 3)]);
-                    }
+    /// Returns every winning line through the tiles the last move changed, or if there is a
+    /// draw. If both players connected enough tiles, which only happens after a pop, the
+    /// player who made the last move wins. A board without moves is checked as a whole.
+    pub(crate) fn check_win(&self) -> CheckWin {
+        let (w, h) = (self.width, self.height);
+        let last = self.history.last();
+        let cells = match last {
+            // every tile of the column moved
+            Some(last) if last.pop => (1..h).map(|y| (last.x, y)).collect(),
+            Some(last) => vec![(last.x, last.y)],
+            None => (1..h)
+                .flat_map(|y| (0..w).map(move |x| (x, y)))
+                .collect::<Vec<(usize, usize)>>(),
+        };
 
-                    if y > 2 {
-                        // check up
-                        if self.board[y - 1][x] == tile
-                            && self.board[y - 2][x] == tile
-                            && self.board[y - 3][x] == tile
-                        {
-                            return Check4::Player([(y, x), (y - 1, x), (y - 2, x), (y - 3, x)]);
-                        }
-
-                        // check up & right
-                        if x < W - 3
-                            && self.board[y - 1][x + 1] == tile
-                            && self.board[y - 2][x + 2] == tile
-                            && self.board[y - 3][x + 3] == tile
-                        {
-                            return Check4::Player([
-                                (y, x),
-                                (y - 1, x + 1),
-                                (y - 2, x + 2),
-                                (y - 3, x + 3),
-                            ]);
-                        }
-
-                        // check up & left
-                        if x > 2
-                            && self.board[y - 1][x - 1] == tile
-                            && self.board[y - 2][x - 2] == tile
-                            && self.board[y - 3][x - 3] == tile
-                        {
-                            return Check4::Player([
-                                (y, x),
-                                (y - 1, x - 1),
-                                (y - 2, x - 2),
-                                (y - 3, x - 3),
-                            ]);
-                        }
-                    }
+        let mut lines: [Vec<Line>; 2] = Default::default();
+        for (x, y) in cells {
+            let player = match self.tile(x, y) {
+                TileType::Player1 => 0,
+                TileType::Player2 => 1,
+                TileType::Empty => continue,
+            };
+            for direction in DIRECTIONS {
+                let line = self.line_through(x, y, direction);
+                if line.len() >= self.connect && !lines[player].contains(&line) {
+                    lines[player].push(line);
                 }
             }
         }
-        if draw {
-            return Check4::Draw;
+        let full = !self.tiles[w..].contains(&TileType::Empty);
+        CheckWin::decide(lines, last.map(|last| last.tile), full)
+    }
+
+    /// Get the connected tiles of the same type through (x, y), going in the given direction.
+    fn line_through(&self, x: usize, y: usize, (dx, dy): (isize, isize)) -> Line {
+        let tile = self.tile(x, y);
+        // row 0 is the status bar, so it can never be part of a line
+        let same = |x: isize, y: isize| {
+            x >= 0
+                && y >= 1
+                && (x as usize) < self.width
+                && (y as usize) < self.height
+                && self.tile(x as usize, y as usize) == tile
+        };
+
+        let (mut x, mut y) = (x as isize, y as isize);
+        while same(x - dx, y - dy) {
+            x -= dx;
+            y -= dy;
         }
-        Check4::None
+        let mut line = Vec::new();
+        while same(x, y) {
+            line.push((y as usize, x as usize));
+            x += dx;
+            y += dy;
+        }
+        line
     }
 
     /// Reset the board to its original state.
-    pub(super) fn reset(&mut self) {
-        self.board = [[TileType::default(); W]; H]
+    pub(crate) fn reset(&mut self) {
+        self.tiles.fill(TileType::default());
+        self.history.clear();
+        self.redo.clear();
+        self.start = TileType::Player1;
+    }
+
+    /// Get the number of columns.
+    pub(crate) fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the height, including the status bar.
+    pub(crate) fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Set the player who makes the first move, or is on move when no moves have been played.
+    pub(crate) fn set_start(&mut self, tile: TileType) {
+        self.start = tile;
+    }
+
+    /// Get the player on move, assuming the players take turns.
+    pub(crate) fn to_move(&self) -> TileType {
+        match self.history.len() % 2 {
+            0 => self.start,
+            _ => self.start.opponent(),
+        }
+    }
+
+    /// Write down the position in the notation read by [`Board::from_str`].
+    pub(crate) fn position(&self) -> String {
+        let symbol = |tile| match tile {
+            TileType::Player1 => 'x',
+            TileType::Player2 => 'o',
+            TileType::Empty => '.',
+        };
+        let rows = (1..self.height)
+            .map(|y| (0..self.width).map(|x| symbol(self.tile(x, y))).collect())
+            .collect::<Vec<String>>();
+        format!("{} {}", rows.join("/"), symbol(self.to_move()))
+    }
+
+    /// Get the position the moves of the history were played from, or [None] if they were
+    /// played from an empty board.
+    pub(crate) fn start_position(&self) -> Option<String> {
+        let mut start = self.clone();
+        while start.undo_last().is_ok() {}
+        start.tiles[self.width..]
+            .iter()
+            .any(|&tile| tile != TileType::Empty)
+            .then(|| start.position())
     }
 
     /// Get the type of tile at position (x, y).
     pub(crate) fn get(&self, x: usize, y: usize) -> GameResult<TileType> {
-        if x >= W || y >= H {
+        if x >= self.width || y >= self.height {
             return Err(Error::InvalidDim);
         }
-
-        Ok(self.board[y][x])
+        Ok(self.tile(x, y))
     }
 
     pub(crate) fn selected_left(&mut self) {
         log::trace!("moving selected left");
         self.unset_active();
-        self.selected += W;
-        self.selected -= 1;
-        self.selected %= W;
+        self.selected = (self.selected + self.width - 1) % self.width;
         self.set_active(self.active_type);
     }
 
     pub(crate) fn selected_right(&mut self) {
         log::trace!("moving selected right");
         self.unset_active();
-        self.selected += 1;
-        self.selected %= W;
+        self.selected = (self.selected + 1) % self.width;
         self.set_active(self.active_type);
     }
 
-    pub(crate) fn place_selected(&mut self) -> GameResult<()> {
-        log::trace!("placing at selected");
-        self.place(self.selected + 1, self.active_type)
-    }
-
     pub(crate) fn selected(&self) -> usize {
         self.selected
     }
 
+    /// Select the given column. The column is zero indexed.
+    pub(crate) fn select(&mut self, col: usize) {
+        self.unset_active();
+        self.selected = col % self.width;
+        self.set_active(self.active_type);
+    }
+
     pub(crate) fn set_active(&mut self, active: TileType) {
         self.active_type = active;
-        self.board[0][self.selected] = active;
+        *self.tile_mut(self.selected, 0) = active;
     }
 
     pub(crate) fn unset_active(&mut self) {
-        self.board[0][self.selected] = TileType::Empty;
+        *self.tile_mut(self.selected, 0) = TileType::Empty;
     }
 
+    /// Undo the last move.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::NoUndos` if no moves have been made.
     pub(crate) fn undo_last(&mut self) -> GameResult<()> {
-        match self.last_move {
-            Some((x, y, t)) => {
-                self.board[y][x] = t;
-                self.last_move = None;
+        match self.history.pop() {
+            Some(last) => {
+                let x = last.x;
+                if last.pop {
+                    for y in 1..last.y {
+                        *self.tile_mut(x, y) = self.tile(x, y + 1);
+                    }
+                    *self.tile_mut(x, last.y) = last.tile;
+                } else {
+                    *self.tile_mut(x, last.y) = TileType::Empty;
+                }
+                self.redo.push(last);
                 Ok(())
             }
             None => Err(Error::NoUndos),
         }
     }
+
+    /// Redo the last undone move.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::NoRedos` if there are no undone moves left.
+    pub(crate) fn redo_last(&mut self) -> GameResult<()> {
+        match self.redo.pop() {
+            Some(next) if next.pop => self.pop_tile(next.x + 1, next.tile),
+            Some(next) => self.drop_tile(next.x + 1, next.tile),
+            None => Err(Error::NoRedos),
+        }
+    }
+
+    /// Get the moves played so far, in order.
+    pub(crate) fn history(&self) -> &[Played] {
+        &self.history
+    }
 }
 
-impl<const W: usize, const H: usize> std::fmt::Display for Board<W, H> {
+/// The standard board of 7 columns and 6 rows.
+impl Default for Board {
+    fn default() -> Self {
+        Self::new(7, 7).expect("the standard board is valid")
+    }
+}
+
+impl std::fmt::Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "#")?;
-        for x in 1..=W {
+        for x in 1..=self.width {
             if x < 10 {
                 write!(f, "-{x}-")?;
             } else if x < 100 {
@@ -201,10 +399,10 @@ impl<const W: usize, const H: usize> std::fmt::Display for Board<W, H> {
             }
         }
         writeln!(f, "#")?;
-        for y in 1..H {
+        for y in 1..self.height {
             write!(f, "|")?;
-            for x in 0..W {
-                match self.board[y][x] {
+            for x in 0..self.width {
+                match self.tile(x, y) {
                     TileType::Empty => write!(f, " . ")?,
                     TileType::Player1 => write!(f, " x ")?,
                     TileType::Player2 => write!(f, " o ")?,
@@ -213,9 +411,340 @@ impl<const W: usize, const H: usize> std::fmt::Display for Board<W, H> {
             writeln!(f, "|")?;
         }
         write!(f, "#")?;
-        for _ in 1..=W {
+        for _ in 1..=self.width {
             write!(f, "---")?;
         }
         writeln!(f, "#")
     }
 }
+
+/// Read a position like `......./......./......./......./...o.../..xxo.. x`.
+///
+/// The rows are written from the top down and separated by `/`, using `x` for tiles of player 1,
+/// `o` for tiles of player 2 and `.` for empty spots. After a space follows the player on move.
+/// The position has to be reachable by the players taking turns dropping tiles, with either
+/// player going first.
+impl FromStr for Board {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rows, start) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| Error::InvalidPosition(String::from("missing the player on move")))?;
+        let start = match start.trim() {
+            "x" => TileType::Player1,
+            "o" => TileType::Player2,
+            start => {
+                return Err(Error::InvalidPosition(format!(
+                    "unknown player on move '{start}'"
+                )))
+            }
+        };
+
+        let rows = rows.split('/').collect::<Vec<&str>>();
+        let width = rows[0].chars().count();
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(Error::InvalidPosition(format!(
+                    "row {} has {} spots, the top row has {width}",
+                    y + 1,
+                    row.chars().count()
+                )));
+            }
+        }
+        let mut board =
+            Self::new(width, rows.len() + 1).map_err(|_| Error::PositionSize(width, rows.len()))?;
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                *board.tile_mut(x, y + 1) = match c {
+                    'x' => TileType::Player1,
+                    'o' => TileType::Player2,
+                    '.' => TileType::Empty,
+                    c => {
+                        return Err(Error::InvalidPosition(format!(
+                            "unknown tile '{c}' in row {}",
+                            y + 1
+                        )))
+                    }
+                };
+            }
+        }
+        for x in 0..width {
+            let floating = (2..board.height).any(|y| {
+                board.tile(x, y) == TileType::Empty && board.tile(x, y - 1) != TileType::Empty
+            });
+            if floating {
+                return Err(Error::FloatingTile(x + 1));
+            }
+        }
+
+        // the player on move has as many tiles as the other player, or one less
+        let count = |tile| board.tiles[width..].iter().filter(|&&t| t == tile).count();
+        let (own, other) = (count(start), count(start.opponent()));
+        if own != other && own + 1 != other {
+            return Err(match start {
+                TileType::Player1 => Error::TileCount(own, other),
+                _ => Error::TileCount(other, own),
+            });
+        }
+        board.start = start;
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Describe the playable rows like a position, without the player on move.
+    fn rows(board: &Board) -> String {
+        board.position().split(' ').next().unwrap().to_string()
+    }
+
+    #[test]
+    fn reads_what_it_writes() {
+        let positions = [
+            "......./......./......./......./......./....... x",
+            "......./......./......./......./......./....... o",
+            "......./......./......./......./...o.../..xxo.. x",
+            "......./......./......./......./...x.../..oxo.. x",
+            "......./......./......./......./......./...x... o",
+            "......./......./......./......./......./...o... x",
+            "..../..../..../.... x",
+        ];
+        for position in positions {
+            let board = position.parse::<Board>().unwrap();
+            assert_eq!(board.position(), position);
+        }
+
+        // the player on move follows the player who started
+        let mut board = Board::default();
+        board.set_start(TileType::Player2);
+        board.place(4, TileType::Player2).unwrap();
+        board.place(4, TileType::Player1).unwrap();
+        board.place(5, TileType::Player2).unwrap();
+        let position = board.position();
+        assert_eq!(
+            position,
+            "......./......./......./......./...x.../...oo.. x"
+        );
+        assert_eq!(
+            position.parse::<Board>().unwrap().to_move(),
+            TileType::Player1
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_positions() {
+        let parse = |position: &str| position.parse::<Board>().unwrap_err();
+        assert!(matches!(
+            parse("..../..../.... x"),
+            Error::PositionSize(4, 3)
+        ));
+        assert!(matches!(
+            parse(".../.../.../.../... x"),
+            Error::PositionSize(3, 5)
+        ));
+        assert!(matches!(
+            parse("......./......./......./......./...x.../....... o"),
+            Error::FloatingTile(4)
+        ));
+        assert!(matches!(
+            parse("......./......./......./......./......./x...... x"),
+            Error::TileCount(1, 0)
+        ));
+        assert!(matches!(
+            parse("......./......./......./......./......./xxxo... o"),
+            Error::TileCount(3, 1)
+        ));
+        assert!(matches!(
+            parse("......./......./......./......./......./...o... o"),
+            Error::TileCount(0, 1)
+        ));
+        assert!(matches!(
+            parse("......./......./......./......./......./......."),
+            Error::InvalidPosition(_)
+        ));
+    }
+
+    #[test]
+    fn places_on_top_of_the_column() {
+        let mut board = Board::new(5, 5).unwrap();
+        board.place(2, TileType::Player1).unwrap();
+        board.place(2, TileType::Player2).unwrap();
+        board.place(5, TileType::Player1).unwrap();
+        assert_eq!(rows(&board), "...../...../.o.../.x..x");
+
+        assert!(matches!(
+            board.place(0, TileType::Player2),
+            Err(Error::InvalidColumn)
+        ));
+        assert!(matches!(
+            board.place(6, TileType::Player2),
+            Err(Error::InvalidColumn)
+        ));
+        assert!(matches!(
+            board.place(1, TileType::Empty),
+            Err(Error::InvalidType)
+        ));
+        board.place(2, TileType::Player2).unwrap();
+        board.place(2, TileType::Player1).unwrap();
+        assert!(matches!(
+            board.place(2, TileType::Player2),
+            Err(Error::ColumnFull)
+        ));
+        assert_eq!(board.history().len(), 5);
+    }
+
+    #[test]
+    fn pops_the_bottom_tile() {
+        let mut board = "...../...../.o.../.x..o x".parse::<Board>().unwrap();
+        board.pop(2, TileType::Player1).unwrap();
+        assert_eq!(rows(&board), "...../...../...../.o..o");
+        assert!(board.history()[0].pop);
+
+        // only an own tile can be popped
+        assert!(matches!(
+            board.pop(5, TileType::Player1),
+            Err(Error::CantPop)
+        ));
+        assert!(matches!(
+            board.pop(1, TileType::Player2),
+            Err(Error::CantPop)
+        ));
+        assert!(matches!(
+            board.pop(6, TileType::Player2),
+            Err(Error::InvalidColumn)
+        ));
+        assert!(matches!(
+            board.pop(2, TileType::Empty),
+            Err(Error::InvalidType)
+        ));
+        assert_eq!(board.history().len(), 1);
+    }
+
+    #[test]
+    fn undoes_and_redoes_a_pop() {
+        let mut board = "......./......./......./x....../oxx..../xoo...o x"
+            .parse::<Board>()
+            .unwrap();
+        let before = rows(&board);
+        board.pop(1, TileType::Player1).unwrap();
+        let popped = rows(&board);
+        assert_eq!(popped, "......./......./......./......./xxx..../ooo...o");
+
+        board.undo_last().unwrap();
+        assert_eq!(rows(&board), before);
+        assert!(board.history().is_empty());
+        board.redo_last().unwrap();
+        assert_eq!(rows(&board), popped);
+        assert!(board.history()[0].pop);
+    }
+
+    #[test]
+    fn pop_connecting_both_players_wins_for_the_popper() {
+        let mut board = "......./......./......./x....../oxxx.../xooo..o x"
+            .parse::<Board>()
+            .unwrap();
+        assert!(matches!(board.check_win(), CheckWin::None));
+        board.pop(1, TileType::Player1).unwrap();
+        match board.check_win() {
+            CheckWin::Player(lines) => {
+                assert_eq!(lines, vec![vec![(5, 0), (5, 1), (5, 2), (5, 3)]]);
+            }
+            other => panic!("expected a win, got {other:?}"),
+        }
+
+        // the same pop by the other player
+        let mut board = "......./......./......./o....../xooo.../oxxx..x o"
+            .parse::<Board>()
+            .unwrap();
+        board.pop(1, TileType::Player2).unwrap();
+        match board.check_win() {
+            CheckWin::Player(lines) => {
+                assert_eq!(lines, vec![vec![(5, 0), (5, 1), (5, 2), (5, 3)]]);
+            }
+            other => panic!("expected a win, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn connects_3_and_5_in_every_direction() {
+        for connect in [3, 5] {
+            // the tiles of the line as (column, height above the bottom), left to right
+            let directions: [Vec<(usize, usize)>; 4] = [
+                (0..connect).map(|i| (2 + i, 0)).collect(),
+                (0..connect).map(|i| (2, i)).collect(),
+                (0..connect).map(|i| (2 + i, i)).collect(),
+                (0..connect).map(|i| (2 + i, connect - 1 - i)).collect(),
+            ];
+            for line in directions {
+                let mut board = Board::default();
+                board.set_connect(connect).unwrap();
+                let mut heights = [0; 7];
+                for (i, &(col, height)) in line.iter().enumerate() {
+                    // the opponent fills the column up to the tile
+                    while heights[col - 1] < height {
+                        board.place(col, TileType::Player2).unwrap();
+                        heights[col - 1] += 1;
+                    }
+                    assert!(
+                        matches!(board.check_win(), CheckWin::None),
+                        "connect {connect}: {line:?} won early"
+                    );
+                    board.place(col, TileType::Player1).unwrap();
+                    heights[col - 1] += 1;
+                    if i + 1 < connect {
+                        assert!(matches!(board.check_win(), CheckWin::None));
+                    }
+                }
+                match board.check_win() {
+                    CheckWin::Player(lines) => {
+                        assert_eq!(lines.len(), 1, "connect {connect}: {line:?}");
+                        assert_eq!(lines[0].len(), connect, "connect {connect}: {line:?}");
+                        assert_eq!(
+                            board.get(lines[0][0].1, lines[0][0].0).unwrap(),
+                            TileType::Player1
+                        );
+                    }
+                    other => panic!("connect {connect}: {line:?} gave {other:?}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn undoes_and_redoes_every_move() {
+        let mut board = Board::new(7, 7).unwrap();
+        let mut tile = TileType::Player1;
+        let mut played = vec![rows(&board)];
+        for col in [4, 4, 3, 5, 1] {
+            board.place(col, tile).unwrap();
+            played.push(rows(&board));
+            tile = tile.opponent();
+        }
+
+        for position in played.iter().rev().skip(1) {
+            board.undo_last().unwrap();
+            assert_eq!(&rows(&board), position);
+        }
+        assert!(matches!(board.undo_last(), Err(Error::NoUndos)));
+        assert!(board.history().is_empty());
+
+        for position in played.iter().skip(1) {
+            board.redo_last().unwrap();
+            assert_eq!(&rows(&board), position);
+        }
+        assert!(matches!(board.redo_last(), Err(Error::NoRedos)));
+        assert_eq!(board.history().len(), 5);
+
+        // a new move after undoing leaves nothing to redo
+        board.undo_last().unwrap();
+        board.undo_last().unwrap();
+        board.place(7, TileType::Player1).unwrap();
+        assert!(matches!(board.redo_last(), Err(Error::NoRedos)));
+        assert_eq!(board.history().len(), 4);
+    }
+}
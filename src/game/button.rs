@@ -1,91 +1,299 @@
-use c4_display::DisplayResult;
-use rppal::gpio::{Gpio, InputPin, Level, Trigger};
 use std::{
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
-    },
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
-// async
+#[cfg(feature = "gpio")]
+use rppal::gpio::{Gpio, InputPin, Trigger};
+#[cfg(feature = "gpio")]
+use std::sync::atomic::{AtomicBool, Ordering};
 
-#[derive(Debug)]
-pub(super) struct ButtonsAsync {
-    // directions: [Direction; 5],
-    pub up: ButtonAsync,
-    pub down: ButtonAsync,
-    pub left: ButtonAsync,
-    pub right: ButtonAsync,
-    pub center: ButtonAsync,
-}
-
-impl ButtonsAsync {
-    pub(super) fn new(up: u8, down: u8, left: u8, right: u8, center: u8) -> DisplayResult<Self> {
-        Ok(Self {
-            up: ButtonAsync::new(up)?,
-            down: ButtonAsync::new(down)?,
-            left: ButtonAsync::new(left)?,
-            right: ButtonAsync::new(right)?,
-            center: ButtonAsync::new(center)?,
-        })
+#[cfg(feature = "gpio")]
+use crate::game::error::GameResult;
+use crate::game::input::InputValue;
+
+/// Presses closer together than this count as a single press.
+pub(super) const DEBOUNCE: Duration = Duration::from_millis(130);
+
+/// Time between two polls of the buttons.
+pub(super) const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// How long a simulated button is held down when pressed.
+const TAP: Duration = Duration::from_millis(50);
+
+/// How long the down button has to be held to count as a hold.
+pub(super) const HOLD: Duration = Duration::from_millis(600);
+
+/// One of the push buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    /// Button pointing up.
+    Up,
+    /// Button pointing down.
+    Down,
+    /// Button pointing left.
+    Left,
+    /// Button pointing right.
+    Right,
+    /// Button in the center.
+    Center,
+}
+
+/// Something that reports the state of a single push button.
+///
+/// The time is passed in by the caller, so sources that don't depend on real hardware behave
+/// the same on every run.
+pub trait ButtonSource: Send {
+    /// Check if the button got pressed since the last call.
+    fn pressed(&mut self, now: Instant) -> bool;
+
+    /// Check if the button is being held down.
+    fn held(&self, now: Instant) -> bool;
+}
+
+/// Turns falling edges of a bouncing button into single presses.
+#[derive(Debug, Default)]
+pub(super) struct Debounce {
+    last_edge: Option<Instant>,
+}
+
+impl Debounce {
+    /// Register a falling edge. Returns if the edge is a new press.
+    pub(super) fn edge(&mut self, at: Instant) -> bool {
+        let press = match self.last_edge {
+            Some(last) => at.saturating_duration_since(last) > DEBOUNCE,
+            None => true,
+        };
+        self.last_edge = Some(at);
+        press
+    }
+}
+
+/// The five push buttons of the board.
+pub struct Buttons {
+    up: Box<dyn ButtonSource>,
+    down: Box<dyn ButtonSource>,
+    left: Box<dyn ButtonSource>,
+    right: Box<dyn ButtonSource>,
+    center: Box<dyn ButtonSource>,
+    /// When the down button was first seen held, and if the hold has been reported.
+    down_held: Option<(Instant, bool)>,
+}
+
+impl Buttons {
+    /// Create buttons from their sources.
+    pub fn new(
+        up: Box<dyn ButtonSource>,
+        down: Box<dyn ButtonSource>,
+        left: Box<dyn ButtonSource>,
+        right: Box<dyn ButtonSource>,
+        center: Box<dyn ButtonSource>,
+    ) -> Self {
+        Self {
+            up,
+            down,
+            left,
+            right,
+            center,
+            down_held: None,
+        }
+    }
+
+    /// Use the buttons connected to the given gpio pins.
+    #[cfg(feature = "gpio")]
+    pub fn gpio(up: u8, down: u8, left: u8, right: u8, center: u8) -> GameResult<Self> {
+        Ok(Self::new(
+            Box::new(ButtonAsync::new(up)?),
+            Box::new(ButtonAsync::new(down)?),
+            Box::new(ButtonAsync::new(left)?),
+            Box::new(ButtonAsync::new(right)?),
+            Box::new(ButtonAsync::new(center)?),
+        ))
+    }
+
+    /// Get the inputs of all buttons pressed since the last poll.
+    ///
+    /// Holding the down button for [`HOLD`] gives a single `InputValue::BtnHoldDown`, besides
+    /// the press.
+    pub(super) fn poll(&mut self, now: Instant) -> Vec<InputValue> {
+        let mut inputs = Vec::new();
+        self.down_held = match (self.down.held(now), self.down_held) {
+            (false, _) => None,
+            (true, None) => Some((now, false)),
+            (true, Some((since, false))) if now.saturating_duration_since(since) >= HOLD => {
+                inputs.push(InputValue::BtnHoldDown);
+                Some((since, true))
+            }
+            (true, held) => held,
+        };
+
+        let pressed = [
+            (&mut self.up, InputValue::BtnUp),
+            (&mut self.down, InputValue::BtnDown),
+            (&mut self.left, InputValue::BtnLeft),
+            (&mut self.right, InputValue::BtnRight),
+            (&mut self.center, InputValue::BtnCenter),
+        ]
+        .into_iter()
+        .filter_map(|(button, input)| button.pressed(now).then_some(input));
+        pressed.chain(inputs).collect()
     }
 }
 
+// async
+
+/// Button on a gpio pin, detecting presses with interrupts.
+#[cfg(feature = "gpio")]
 #[derive(Debug)]
 pub(super) struct ButtonAsync {
-    pub pin: InputPin,
-    /// true = pressed, false = released
-    pub last_state: bool,
-    pub triggered: Arc<AtomicBool>,
-    // pub last_trigger: Arc<Mutex<Instant>>,
+    pin: InputPin,
+    triggered: Arc<AtomicBool>,
 }
 
+#[cfg(feature = "gpio")]
 impl ButtonAsync {
-    fn new(pin: u8) -> DisplayResult<Self> {
+    fn new(pin: u8) -> GameResult<Self> {
         let mut pin = Gpio::new()?.get(pin)?.into_input_pullup();
-        let last_state = match pin.read() {
-            Level::High => false,
-            Level::Low => true,
-        };
 
         let triggered = Arc::new(AtomicBool::new(false));
-        let last_trigger = Arc::new(Mutex::new(Instant::now()));
+        let debounce = Mutex::new(Debounce::default());
 
         let triggered_thread = triggered.clone();
-        // let last_trigger_thread = last_trigger.clone();
 
         pin.set_async_interrupt(Trigger::FallingEdge, move |_| {
-            if !triggered_thread.load(Ordering::SeqCst) {
-                if last_trigger.lock().unwrap().elapsed() > Duration::from_millis(130) {
-                    triggered_thread.store(true, Ordering::SeqCst);
-                }
-                let mut last_trigger = last_trigger.lock().unwrap();
-                *last_trigger = Instant::now();
+            if !triggered_thread.load(Ordering::SeqCst)
+                && debounce.lock().unwrap().edge(Instant::now())
+            {
+                triggered_thread.store(true, Ordering::SeqCst);
             }
         })
         .unwrap();
 
-        Ok(Self {
-            pin,
-            last_state,
-            triggered,
-            // last_trigger,
-        })
+        Ok(Self { pin, triggered })
     }
+}
 
-    pub(super) fn read(&mut self) -> bool {
+#[cfg(feature = "gpio")]
+impl ButtonSource for ButtonAsync {
+    fn pressed(&mut self, _now: Instant) -> bool {
         self.triggered.swap(false, Ordering::SeqCst)
     }
+
+    fn held(&self, _now: Instant) -> bool {
+        self.pin.is_low()
+    }
+}
+
+// simulated
+
+/// A single press of a simulated button.
+#[derive(Debug, Clone, Copy)]
+struct Press {
+    button: Button,
+    at: Instant,
+    hold: Duration,
+}
+
+/// Presses of simulated buttons.
+///
+/// Clones share the same presses, so presses can be added while the buttons are in use, for
+/// example from another thread.
+#[derive(Debug, Clone, Default)]
+pub struct ButtonScript {
+    presses: Arc<Mutex<Vec<Press>>>,
+}
+
+impl ButtonScript {
+    /// Create an empty script.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Press a button at the given time, releasing it shortly after.
+    pub fn press(&self, button: Button, at: Instant) -> &Self {
+        self.hold(button, at, TAP)
+    }
+
+    /// Hold a button down for the given time.
+    pub fn hold(&self, button: Button, at: Instant, hold: Duration) -> &Self {
+        self.presses
+            .lock()
+            .unwrap()
+            .push(Press { button, at, hold });
+        self
+    }
+
+    /// Create buttons driven by this script.
+    pub fn buttons(&self) -> Buttons {
+        let button = |button| -> Box<dyn ButtonSource> {
+            Box::new(SimulatedButton {
+                button,
+                script: self.clone(),
+                debounce: Debounce::default(),
+                last_poll: None,
+            })
+        };
+        Buttons::new(
+            button(Button::Up),
+            button(Button::Down),
+            button(Button::Left),
+            button(Button::Right),
+            button(Button::Center),
+        )
+    }
+}
+
+/// Button pressed according to a [`ButtonScript`].
+#[derive(Debug)]
+pub struct SimulatedButton {
+    button: Button,
+    script: ButtonScript,
+    debounce: Debounce,
+    /// Time of the last call to `pressed`.
+    last_poll: Option<Instant>,
+}
+
+impl ButtonSource for SimulatedButton {
+    fn pressed(&mut self, now: Instant) -> bool {
+        let since = self.last_poll.replace(now);
+        let mut edges = self
+            .script
+            .presses
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|press| press.button == self.button && press.at <= now)
+            .filter(|press| since.is_none_or(|since| press.at > since))
+            .map(|press| press.at)
+            .collect::<Vec<Instant>>();
+        edges.sort();
+
+        let mut pressed = false;
+        for at in edges {
+            // like the interrupt, edges are ignored while a press is waiting to be read
+            if !pressed && self.debounce.edge(at) {
+                pressed = true;
+            }
+        }
+        pressed
+    }
+
+    fn held(&self, now: Instant) -> bool {
+        self.script.presses.lock().unwrap().iter().any(|press| {
+            press.button == self.button && press.at <= now && now < press.at + press.hold
+        })
+    }
 }
 
 // synchronous buttons (inferior)
+#[cfg(feature = "gpio")]
 #[allow(dead_code)]
 mod sync_button {
     use std::time::{Duration, Instant};
 
-    use c4_display::DisplayResult;
     use rppal::gpio::{Gpio, InputPin, Level};
+
+    use crate::game::error::GameResult;
     #[derive(Debug)]
     pub(super) struct Buttons {
         // directions: [Direction; 5],
@@ -106,13 +314,7 @@ mod sync_button {
     }
 
     impl Buttons {
-        pub(super) fn new(
-            up: u8,
-            down: u8,
-            left: u8,
-            right: u8,
-            center: u8,
-        ) -> DisplayResult<Self> {
+        pub(super) fn new(up: u8, down: u8, left: u8, right: u8, center: u8) -> GameResult<Self> {
             Ok(Self {
                 up: Button::new(up)?,
                 down: Button::new(down)?,
@@ -124,7 +326,7 @@ mod sync_button {
     }
 
     impl Button {
-        fn new(pin: u8) -> DisplayResult<Self> {
+        fn new(pin: u8) -> GameResult<Self> {
             let pin = Gpio::new()?.get(pin)?.into_input_pullup();
             let last_state = match pin.read() {
                 Level::High => false,
@@ -144,11 +346,11 @@ mod sync_button {
         pub(super) fn read(&mut self) -> bool {
             let mut vals = [false; 11];
 
-            for x in 0..11 {
+            for val in vals.iter_mut() {
                 if self.pin.is_low() {
-                    vals[x] = true;
+                    *val = true;
                 }
-                c4_display::spin_wait(Duration::from_millis(2));
+                std::thread::sleep(Duration::from_millis(2));
             }
 
             let mut counts = 0;
@@ -174,3 +376,82 @@ mod sync_button {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    /// Poll the buttons every [`POLL_INTERVAL`] for the given time, like the input thread does.
+    ///
+    /// Returns the inputs with the time since `start` they were polled at.
+    fn poll_for(buttons: &mut Buttons, start: Instant, time: Duration) -> Vec<(Duration, String)> {
+        let mut inputs = Vec::new();
+        let mut since = Duration::ZERO;
+        while since <= time {
+            for input in buttons.poll(start + since) {
+                inputs.push((since, format!("{input:?}")));
+            }
+            since += POLL_INTERVAL;
+        }
+        inputs
+    }
+
+    fn inputs(inputs: &[(u64, &str)]) -> Vec<(Duration, String)> {
+        inputs
+            .iter()
+            .map(|&(at, input)| (ms(at), input.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn debounces_presses() {
+        let start = Instant::now();
+        let script = ButtonScript::new();
+        script
+            .press(Button::Left, start + ms(10))
+            .press(Button::Left, start + ms(10) + DEBOUNCE)
+            .press(Button::Left, start + ms(11) + DEBOUNCE * 2);
+        let polled = poll_for(&mut script.buttons(), start, ms(400));
+        assert_eq!(polled, inputs(&[(20, "BtnLeft"), (280, "BtnLeft")]));
+    }
+
+    #[test]
+    fn reports_presses_on_the_next_poll() {
+        let start = Instant::now();
+        let script = ButtonScript::new();
+        script
+            .press(Button::Up, start + ms(1))
+            .press(Button::Center, start + POLL_INTERVAL)
+            .press(Button::Right, start + POLL_INTERVAL + ms(1));
+        let polled = poll_for(&mut script.buttons(), start, ms(100));
+        assert_eq!(
+            polled,
+            inputs(&[(20, "BtnUp"), (20, "BtnCenter"), (40, "BtnRight")])
+        );
+    }
+
+    #[test]
+    fn holds_down() {
+        let start = Instant::now();
+        let script = ButtonScript::new();
+        script.hold(Button::Down, start + ms(10), ms(1000));
+        let polled = poll_for(&mut script.buttons(), start, ms(1500));
+        // the hold counts from the first poll that sees the button down
+        assert_eq!(polled, inputs(&[(20, "BtnDown"), (620, "BtnHoldDown")]));
+    }
+
+    #[test]
+    fn short_hold_is_a_press() {
+        let start = Instant::now();
+        let script = ButtonScript::new();
+        script
+            .hold(Button::Down, start + ms(10), HOLD - ms(10))
+            .press(Button::Down, start + ms(1000));
+        let polled = poll_for(&mut script.buttons(), start, ms(1500));
+        assert_eq!(polled, inputs(&[(20, "BtnDown"), (1000, "BtnDown")]));
+    }
+}
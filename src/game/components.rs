@@ -1,24 +1,111 @@
+use crate::{display::Color, game::error::Error};
+
 /// The type of tiles that can be on the board.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum TileType {
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TileType {
+    /// Tile of player 1.
     Player1,
+    /// Tile of player 2.
     Player2,
+    /// No tile.
+    #[default]
     Empty,
 }
 
+/// Result of a game played without display or input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameRecord {
+    /// Tile of the winning player, [None] if the game was a draw.
+    pub winner: Option<TileType>,
+    /// Number of moves played.
+    pub moves: usize,
+}
+
+/// Number of tiles a player has to connect to win, unless the game is set up otherwise.
+pub const DEFAULT_CONNECT: usize = 4;
+
+/// Fewest tiles a game can ask to connect.
+pub(crate) const MIN_CONNECT: usize = 3;
+
+/// The rules a game is played by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rules {
+    /// Players take turns dropping a tile in a column.
+    #[default]
+    Standard,
+    /// Instead of dropping a tile, a player may pop one of their own tiles out of the bottom
+    /// row, making the tiles above it fall down.
+    ///
+    /// A pop can complete a line for both players at once, in which case the player who popped
+    /// wins. Like in the standard rules, a full board is a draw.
+    PopOut,
+}
+
+/// A move of a player. The column is 1 indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    /// A tile dropped in the column.
+    Place(usize),
+    /// A tile popped out of the bottom of the column.
+    Pop(usize),
+}
+
+/// A move as stored in the history of the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Played {
+    /// Column of the move, zero indexed.
+    pub(crate) x: usize,
+    /// Row the tile landed in, or the bottom row for a pop.
+    pub(crate) y: usize,
+    /// Tile that was placed or popped.
+    pub(crate) tile: TileType,
+    /// If the tile was popped out instead of placed.
+    pub(crate) pop: bool,
+}
+
+impl Played {
+    /// The move as seen by the players.
+    pub(crate) fn to_move(self) -> Move {
+        match self.pop {
+            true => Move::Pop(self.x + 1),
+            false => Move::Place(self.x + 1),
+        }
+    }
+}
+
+/// Connected tiles of a single player, as (y, x) positions in order along the line.
+pub(crate) type Line = Vec<(usize, usize)>;
+
 /// If there has been a winner or a draw.
 #[derive(Debug)]
-pub(crate) enum Check4 {
-    Player([(usize, usize); 4]),
+pub(crate) enum CheckWin {
+    /// A player connected enough tiles. Contains every winning line, never empty.
+    Player(Vec<Line>),
     Draw,
     None,
 }
 
+impl CheckWin {
+    /// Decide the outcome from the winning lines of player 1 and player 2.
+    ///
+    /// If both players have a line, which only happens after a pop, the player who made the
+    /// last move wins.
+    pub(crate) fn decide(lines: [Vec<Line>; 2], last: Option<TileType>, full: bool) -> Self {
+        let [lines1, lines2] = lines;
+        match (lines1.is_empty(), lines2.is_empty()) {
+            (false, false) if last == Some(TileType::Player2) => Self::Player(lines2),
+            (false, _) => Self::Player(lines1),
+            (true, false) => Self::Player(lines2),
+            (true, true) if full => Self::Draw,
+            (true, true) => Self::None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(super) enum ActivePlayer {
     Player1,
     Player2,
-    Ai,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -62,7 +149,6 @@ impl Players {
         match self.active {
             ActivePlayer::Player1 => self.player1.clone(),
             ActivePlayer::Player2 => self.player2.clone(),
-            ActivePlayer::Ai => Player::ai_placeholder(),
         }
         // if self.active {
         //     self.player1.clone()
@@ -71,28 +157,15 @@ impl Players {
         // }
     }
 
-    /// Add a point to the active player.
-    pub(super) fn scored(&mut self) {
-        match self.active {
-            ActivePlayer::Player1 => self.player1.score += 1,
-            ActivePlayer::Player2 => self.player2.score += 1,
-            ActivePlayer::Ai => (), // TODO track ai score
-        }
+    /// Add a point to the player with the given tile.
+    pub(super) fn scored(&mut self, tile: TileType) {
+        self.get_mut(tile).score += 1;
     }
 
     pub(super) fn swap(&mut self) {
         self.active = match self.active {
             ActivePlayer::Player1 => ActivePlayer::Player2,
             ActivePlayer::Player2 => ActivePlayer::Player1,
-            ActivePlayer::Ai => unimplemented!(),
-        }
-    }
-
-    pub(super) fn swap_ai(&mut self) {
-        self.active = match self.active {
-            ActivePlayer::Player1 => ActivePlayer::Ai,
-            ActivePlayer::Ai => ActivePlayer::Player1,
-            ActivePlayer::Player2 => unimplemented!(),
         }
     }
 
@@ -104,6 +177,22 @@ impl Players {
     pub(super) fn set_active(&mut self, active: ActivePlayer) {
         self.active = active;
     }
+
+    /// Get the player with the given tile.
+    pub(super) fn get(&self, tile: TileType) -> &Player {
+        match tile {
+            TileType::Player2 => &self.player2,
+            _ => &self.player1,
+        }
+    }
+
+    /// Get the player with the given tile mutably.
+    pub(super) fn get_mut(&mut self, tile: TileType) -> &mut Player {
+        match tile {
+            TileType::Player2 => &mut self.player2,
+            _ => &mut self.player1,
+        }
+    }
 }
 
 /// A single player
@@ -111,29 +200,53 @@ impl Players {
 pub(super) struct Player {
     pub name: String,
     pub score: usize,
-    pub color: c4_display::LedColor,
+    pub color: Color,
     pub tile: TileType,
 }
 
-impl Player {
-    pub(crate) fn ai_placeholder() -> Self {
-        Self {
-            name: "AI".to_string(),
-            score: 0,
-            color: c4_display::LedColor::Yellow,
-            tile: TileType::Player2,
+impl TileType {
+    /// Get the tile type of the opposing player.
+    pub(crate) fn opponent(self) -> Self {
+        match self {
+            Self::Player1 => Self::Player2,
+            Self::Player2 => Self::Player1,
+            Self::Empty => Self::Empty,
         }
     }
 }
 
-impl Default for TileType {
-    fn default() -> Self {
-        Self::Empty
+impl std::fmt::Display for Player {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
     }
 }
 
-impl std::fmt::Display for Player {
+impl std::str::FromStr for Rules {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().trim() {
+            "standard" | "normal" => Ok(Self::Standard),
+            "popout" | "pop-out" | "pop out" => Ok(Self::PopOut),
+            str => Err(Error::InvalidInput(str.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Rules {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.name)
+        match self {
+            Rules::Standard => write!(f, "standard"),
+            Rules::PopOut => write!(f, "popout"),
+        }
+    }
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Move::Place(col) => write!(f, "{col}"),
+            Move::Pop(col) => write!(f, "p{col}"),
+        }
     }
 }
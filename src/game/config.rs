@@ -0,0 +1,457 @@
+use std::{path::Path, str::FromStr, time::Duration};
+
+use crate::{
+    display::Color,
+    game::{
+        components::{Rules, DEFAULT_CONNECT, MIN_CONNECT},
+        error::{Error, GameResult},
+    },
+};
+
+/// Board sizes that can be chosen from the menu, as (columns, playable rows).
+///
+/// These are the sizes the led matrix can show. Other sizes can be set in the config, to play
+/// in the terminal. Choosing a size restarts the game, see
+/// [`Game::requested_size`](crate::Game::requested_size).
+pub const BOARD_SIZES: [(usize, usize); 4] = [(6, 5), (7, 6), (8, 7), (9, 7)];
+
+/// Highest gpio pin number of the Raspberry Pi.
+const MAX_PIN: u8 = 27;
+
+/// Gpio pins driving the led matrix, numbered like the BCM pins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayPins {
+    /// Serial input of the shift registers.
+    pub sr_serin: u8,
+    /// Shift register clock.
+    pub sr_srclk: u8,
+    /// Storage register clock.
+    pub sr_rclk: u8,
+    /// Clears the shift registers.
+    pub sr_srclr: u8,
+    /// Output enable of the shift registers.
+    pub sr_oe: u8,
+    /// Address bit 0 of the row decoder.
+    pub dec_a0: u8,
+    /// Address bit 1 of the row decoder.
+    pub dec_a1: u8,
+    /// Address bit 2 of the row decoder.
+    pub dec_a2: u8,
+    /// Latch enable of the row decoder.
+    pub dec_le: u8,
+    /// Enable of the row decoder.
+    pub dec_e1: u8,
+}
+
+impl Default for DisplayPins {
+    fn default() -> Self {
+        Self {
+            sr_serin: 17,
+            sr_srclk: 22,
+            sr_rclk: 23,
+            sr_srclr: 24,
+            sr_oe: 27,
+            dec_a0: 25,
+            dec_a1: 11,
+            dec_a2: 5,
+            dec_le: 6,
+            dec_e1: 10,
+        }
+    }
+}
+
+impl DisplayPins {
+    fn named(&self) -> [(&'static str, u8); 10] {
+        [
+            ("sr_serin", self.sr_serin),
+            ("sr_srclk", self.sr_srclk),
+            ("sr_rclk", self.sr_rclk),
+            ("sr_srclr", self.sr_srclr),
+            ("sr_oe", self.sr_oe),
+            ("dec_a0", self.dec_a0),
+            ("dec_a1", self.dec_a1),
+            ("dec_a2", self.dec_a2),
+            ("dec_le", self.dec_le),
+            ("dec_e1", self.dec_e1),
+        ]
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut u8> {
+        match name {
+            "sr_serin" => Some(&mut self.sr_serin),
+            "sr_srclk" => Some(&mut self.sr_srclk),
+            "sr_rclk" => Some(&mut self.sr_rclk),
+            "sr_srclr" => Some(&mut self.sr_srclr),
+            "sr_oe" => Some(&mut self.sr_oe),
+            "dec_a0" => Some(&mut self.dec_a0),
+            "dec_a1" => Some(&mut self.dec_a1),
+            "dec_a2" => Some(&mut self.dec_a2),
+            "dec_le" => Some(&mut self.dec_le),
+            "dec_e1" => Some(&mut self.dec_e1),
+            _ => None,
+        }
+    }
+}
+
+/// Gpio pins of the push buttons, numbered like the BCM pins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonPins {
+    /// Pin of the up button.
+    pub up: u8,
+    /// Pin of the down button.
+    pub down: u8,
+    /// Pin of the left button.
+    pub left: u8,
+    /// Pin of the right button.
+    pub right: u8,
+    /// Pin of the center button.
+    pub center: u8,
+}
+
+impl Default for ButtonPins {
+    fn default() -> Self {
+        Self {
+            up: 2,
+            down: 4,
+            left: 3,
+            right: 15,
+            center: 14,
+        }
+    }
+}
+
+impl ButtonPins {
+    fn named(&self) -> [(&'static str, u8); 5] {
+        [
+            ("up", self.up),
+            ("down", self.down),
+            ("left", self.left),
+            ("right", self.right),
+            ("center", self.center),
+        ]
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut u8> {
+        match name {
+            "up" => Some(&mut self.up),
+            "down" => Some(&mut self.down),
+            "left" => Some(&mut self.left),
+            "right" => Some(&mut self.right),
+            "center" => Some(&mut self.center),
+            _ => None,
+        }
+    }
+}
+
+/// Settings of a table, read from a config file.
+///
+/// The file is INI style. Every setting is optional, missing ones keep their default:
+///
+/// ```text
+/// [board]
+/// width = 7
+/// height = 6
+/// connect = 4
+/// rules = standard
+///
+/// [display]
+/// refresh_rate = 60
+/// sr_serin = 17
+/// sr_srclk = 22
+/// sr_rclk = 23
+/// sr_srclr = 24
+/// sr_oe = 27
+/// dec_a0 = 25
+/// dec_a1 = 11
+/// dec_a2 = 5
+/// dec_le = 6
+/// dec_e1 = 10
+///
+/// [buttons]
+/// up = 2
+/// down = 4
+/// left = 3
+/// right = 15
+/// center = 14
+///
+/// [colors]
+/// player1 = red
+/// player2 = yellow
+///
+/// [timings]
+/// drop_frame = 100
+/// win_blink = 250
+/// win_blink_interval = 500
+///
+/// [animations]
+/// idle = ./animations/circle.mtxani
+/// ```
+///
+/// Timings are in milliseconds. The height of the board is the number of playable rows. The
+/// rules are `standard` or `popout`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Number of columns.
+    pub width: usize,
+    /// Number of playable rows.
+    pub height: usize,
+    /// Number of tiles a player has to connect to win.
+    pub connect: usize,
+    /// Rules the game is played by.
+    pub rules: Rules,
+    /// Pins driving the led matrix.
+    pub display_pins: DisplayPins,
+    /// Refresh rate of the led matrix in Hz.
+    pub refresh_rate: f64,
+    /// Pins of the push buttons.
+    pub button_pins: ButtonPins,
+    /// Colors of player 1 and player 2.
+    pub colors: [Color; 2],
+    /// How long a falling tile is shown on every row.
+    pub drop_frame: Duration,
+    /// How long the winning tiles are on every blink.
+    pub win_blink: Duration,
+    /// Time between the start of two blinks of the winning tiles.
+    pub win_blink_interval: Duration,
+    /// Animation shown while no round is being played.
+    pub idle_animation: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            width: 7,
+            height: 6,
+            connect: DEFAULT_CONNECT,
+            rules: Rules::Standard,
+            display_pins: DisplayPins::default(),
+            refresh_rate: 60.0,
+            button_pins: ButtonPins::default(),
+            colors: [Color::Red, Color::Yellow],
+            drop_frame: Duration::from_millis(100),
+            win_blink: Duration::from_millis(250),
+            win_blink_interval: Duration::from_millis(500),
+            idle_animation: String::from("./animations/circle.mtxani"),
+        }
+    }
+}
+
+impl Config {
+    /// Read the config from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file can't be read.
+    ///
+    /// Returns `Error::InvalidConfig` if the file is not a valid config.
+    pub fn from_file(path: impl AsRef<Path>) -> GameResult<Self> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Check if the settings make sense together.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidConfig` with the first problem found.
+    pub fn validate(&self) -> GameResult<()> {
+        let invalid = |msg: String| Err(Error::InvalidConfig(msg));
+
+        if self.width < 4 || self.height < 4 {
+            return invalid(format!(
+                "board of {}x{} is too small, it needs at least 4x4",
+                self.width, self.height
+            ));
+        }
+        if self.connect < MIN_CONNECT || self.connect > self.width.max(self.height) {
+            return invalid(format!(
+                "can't connect {} tiles on a {}x{} board",
+                self.connect, self.width, self.height
+            ));
+        }
+        if !self.refresh_rate.is_finite() || self.refresh_rate <= 0.0 {
+            return invalid(format!("refresh rate of {} Hz", self.refresh_rate));
+        }
+
+        let pins = self
+            .display_pins
+            .named()
+            .into_iter()
+            .map(|(name, pin)| (format!("display {name}"), pin))
+            .chain(
+                self.button_pins
+                    .named()
+                    .into_iter()
+                    .map(|(name, pin)| (format!("button {name}"), pin)),
+            )
+            .collect::<Vec<(String, u8)>>();
+        for (i, (name, pin)) in pins.iter().enumerate() {
+            if *pin > MAX_PIN {
+                return invalid(format!("{name} uses pin {pin}, which doesn't exist"));
+            }
+            if let Some((other, _)) = pins[..i].iter().find(|(_, other)| other == pin) {
+                return invalid(format!("{other} and {name} both use pin {pin}"));
+            }
+        }
+
+        if self.colors.contains(&Color::Off) {
+            return invalid(String::from("players need a visible color"));
+        }
+        if self.colors[0] == self.colors[1] {
+            return invalid(format!("both players use {}", self.colors[0]));
+        }
+
+        if self.drop_frame.is_zero() {
+            return invalid(String::from("drop_frame must be longer than 0 ms"));
+        }
+        if self.win_blink.is_zero() || self.win_blink >= self.win_blink_interval {
+            return invalid(String::from(
+                "win_blink must be longer than 0 ms and shorter than win_blink_interval",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Config {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Config::default();
+        let mut section = None;
+
+        for (line, text) in s.lines().enumerate().map(|(i, line)| (i + 1, line.trim())) {
+            let invalid = |msg: &str| Error::InvalidConfig(format!("line {line}: {msg}"));
+            if text.is_empty() || text.starts_with('#') || text.starts_with(';') {
+                continue;
+            }
+
+            if let Some(name) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+                section = match name.trim() {
+                    name @ ("board" | "display" | "buttons" | "colors" | "timings"
+                    | "animations") => Some(name),
+                    name => return Err(invalid(&format!("unknown section [{name}]"))),
+                };
+                continue;
+            }
+
+            let section = section.ok_or_else(|| invalid("expected a section like [board]"))?;
+            let (key, value) = text
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| invalid("expected 'key = value'"))?;
+            let unknown = || invalid(&format!("unknown key '{key}' in [{section}]"));
+            let number = || {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| invalid(&format!("{key} must be a number, not '{value}'")))
+            };
+            let pin = || match value.parse::<u8>() {
+                Ok(pin) if pin <= MAX_PIN => Ok(pin),
+                _ => Err(invalid(&format!(
+                    "{key} must be a pin between 0 and {MAX_PIN}, not '{value}'"
+                ))),
+            };
+
+            match (section, key) {
+                ("board", "width") => config.width = number()? as usize,
+                ("board", "height") => config.height = number()? as usize,
+                ("board", "connect") => config.connect = number()? as usize,
+                ("board", "rules") => {
+                    config.rules = value
+                        .parse()
+                        .map_err(|_| invalid(&format!("unknown rules '{value}'")))?
+                }
+                ("display", "refresh_rate") => {
+                    config.refresh_rate = match value.parse::<f64>() {
+                        Ok(rate) if rate.is_finite() && rate > 0.0 => rate,
+                        _ => return Err(invalid(&format!("invalid refresh rate '{value}'"))),
+                    }
+                }
+                ("display", key) => *config.display_pins.get_mut(key).ok_or_else(unknown)? = pin()?,
+                ("buttons", key) => *config.button_pins.get_mut(key).ok_or_else(unknown)? = pin()?,
+                ("colors", "player1" | "player2") => {
+                    let color = value
+                        .parse()
+                        .map_err(|_| invalid(&format!("unknown color '{value}'")))?;
+                    config.colors[if key == "player1" { 0 } else { 1 }] = color;
+                }
+                ("timings", "drop_frame") => config.drop_frame = Duration::from_millis(number()?),
+                ("timings", "win_blink") => config.win_blink = Duration::from_millis(number()?),
+                ("timings", "win_blink_interval") => {
+                    config.win_blink_interval = Duration::from_millis(number()?)
+                }
+                ("animations", "idle") => config.idle_animation = value.to_string(),
+                _ => return Err(unknown()),
+            }
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get the reason the config is not valid.
+    fn invalid(s: &str) -> String {
+        match s.parse::<Config>() {
+            Err(Error::InvalidConfig(reason)) => reason,
+            other => panic!("expected Error::InvalidConfig, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn reads_settings() {
+        let config = "[board]\nwidth = 9\nheight = 7\nconnect = 5\n\n[timings]\nwin_blink = 100\n"
+            .parse::<Config>()
+            .unwrap();
+        assert_eq!((config.width, config.height, config.connect), (9, 7, 5));
+        assert_eq!(config.win_blink, Duration::from_millis(100));
+        assert_eq!(config.drop_frame, Config::default().drop_frame);
+    }
+
+    #[test]
+    fn rejects_unknown_sections_and_keys() {
+        assert_eq!(
+            invalid("[sound]\nvolume = 3"),
+            "line 1: unknown section [sound]"
+        );
+        assert_eq!(
+            invalid("[board]\nwidth = 7\ndepth = 3"),
+            "line 3: unknown key 'depth' in [board]"
+        );
+        assert_eq!(
+            invalid("[buttons]\nstart = 8"),
+            "line 2: unknown key 'start' in [buttons]"
+        );
+        assert_eq!(
+            invalid("width = 7"),
+            "line 1: expected a section like [board]"
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_pins() {
+        assert_eq!(
+            invalid("[buttons]\nup = 17"),
+            "display sr_serin and button up both use pin 17"
+        );
+        assert_eq!(
+            invalid("[buttons]\nleft = 2"),
+            "button up and button left both use pin 2"
+        );
+    }
+
+    #[test]
+    fn blinks_shorter_than_the_interval() {
+        let blink = |blink: u64, interval: u64| {
+            format!("[timings]\nwin_blink = {blink}\nwin_blink_interval = {interval}")
+        };
+        assert!(blink(499, 500).parse::<Config>().is_ok());
+        let reason = "win_blink must be longer than 0 ms and shorter than win_blink_interval";
+        assert_eq!(invalid(&blink(500, 500)), reason);
+        assert_eq!(invalid(&blink(0, 500)), reason);
+    }
+}
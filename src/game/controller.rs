@@ -0,0 +1,243 @@
+use std::time::Duration;
+
+use crate::{
+    ai::{Ai, AiLevel},
+    game::{
+        board::Board,
+        components::{CheckWin, GameRecord, Move, Rules, TileType},
+        error::GameResult,
+        game::Game,
+    },
+};
+
+/// What a player decided to do on their turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Place a tile in the given column. The column is 1 indexed.
+    Place(usize),
+    /// Pop an own tile out of the bottom of the given column. Only allowed with the
+    /// [`Rules::PopOut`] rules. The column is 1 indexed.
+    Pop(usize),
+    /// Take back the last move.
+    Undo,
+    /// Replay the last move that was taken back.
+    Redo,
+    /// Stop the round.
+    Quit,
+}
+
+/// Something that decides the moves for one of the players.
+pub trait PlayerController {
+    /// Decide what to do on this turn.
+    fn play(&mut self, turn: &mut Turn<'_, '_>) -> Action;
+
+    /// Name to show for the player. Uses the name of the player when [None].
+    fn name(&self) -> Option<String> {
+        None
+    }
+
+    /// If the player is sitting at the table.
+    ///
+    /// Undo and redo skip over the turns of players that are not local.
+    fn is_local(&self) -> bool {
+        false
+    }
+
+    /// If the player is playing along on another board.
+    ///
+    /// Moves can't be undone against a remote player, since both boards would need to agree.
+    fn is_remote(&self) -> bool {
+        false
+    }
+
+    /// Called after the opponent placed a tile in the given column. The column is 1 indexed.
+    fn opponent_moved(&mut self, _col: usize, _tile: TileType) {}
+
+    /// Called after the opponent popped a tile out of the given column. The column is 1
+    /// indexed.
+    fn opponent_popped(&mut self, _col: usize, _tile: TileType) {}
+
+    /// Called after the opponent stopped the round.
+    fn opponent_quit(&mut self) {}
+}
+
+/// A turn of a player, giving access to the board.
+pub struct Turn<'t, 'g> {
+    game: &'t mut Game<'g>,
+}
+
+impl<'t, 'g> Turn<'t, 'g> {
+    pub(super) fn new(game: &'t mut Game<'g>) -> Self {
+        Self { game }
+    }
+
+    /// Get a view of the board.
+    pub fn board(&self) -> BoardView<'_> {
+        BoardView {
+            board: self.game.board(),
+        }
+    }
+
+    /// Get the tile of the player on turn.
+    pub fn tile(&self) -> TileType {
+        self.game.active_tile()
+    }
+
+    /// Get the rules the game is played by.
+    pub fn rules(&self) -> Rules {
+        self.game.rules()
+    }
+}
+
+/// Read only view of the board.
+///
+/// Only the playable rows are visible, (0, 0) being the top left.
+pub struct BoardView<'b> {
+    board: &'b Board,
+}
+
+impl<'b> BoardView<'b> {
+    /// Number of columns.
+    pub fn width(&self) -> usize {
+        self.board.width()
+    }
+
+    /// Number of playable rows.
+    pub fn height(&self) -> usize {
+        self.board.height() - 1
+    }
+
+    /// Get the type of tile at position (x, y). Returns [None] if the position is outside the
+    /// board.
+    pub fn get(&self, x: usize, y: usize) -> Option<TileType> {
+        self.board.get(x, y + 1).ok()
+    }
+
+    /// Check if a tile can be placed in the column. The column is 1 indexed.
+    pub fn can_place(&self, col: usize) -> bool {
+        col >= 1 && self.board.first_empty(col - 1).is_ok()
+    }
+
+    /// Check if the given tile can be popped out of the column. The column is 1 indexed.
+    ///
+    /// This only looks at the board, popping is only allowed with the [`Rules::PopOut`] rules.
+    pub fn can_pop(&self, col: usize, tile: TileType) -> bool {
+        let bottom = self.board.height() - 1;
+        col >= 1 && tile != TileType::Empty && self.board.get(col - 1, bottom).ok() == Some(tile)
+    }
+
+    /// Write down the position, like `......./......./......./......./...o.../..xxo.. x`.
+    ///
+    /// The rows are written from the top down and separated by `/`, using `x` for tiles of
+    /// player 1, `o` for tiles of player 2 and `.` for empty spots. After a space follows the
+    /// player on move.
+    pub fn position(&self) -> String {
+        self.board.position()
+    }
+
+    /// Get all moves played so far, in order.
+    pub fn moves(&self) -> Vec<Move> {
+        self.board
+            .history()
+            .iter()
+            .map(|played| played.to_move())
+            .collect()
+    }
+
+    pub(crate) fn inner(&self) -> &Board {
+        self.board
+    }
+}
+
+/// A player at the table, using the buttons or the terminal.
+#[derive(Debug, Default)]
+pub struct LocalPlayer;
+
+impl PlayerController for LocalPlayer {
+    fn play(&mut self, turn: &mut Turn<'_, '_>) -> Action {
+        turn.game.local_turn()
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+}
+
+/// A computer player.
+pub struct AiPlayer {
+    ai: Ai,
+}
+
+impl AiPlayer {
+    /// Create a new ai player at the given difficulty level.
+    pub fn new(level: AiLevel) -> Self {
+        let mut ai = Ai::new();
+        ai.set_level(level);
+        Self { ai }
+    }
+
+    /// Search the given number of moves ahead, overriding the depth of the difficulty level.
+    ///
+    /// A depth of 0 makes the ai only look at the next move.
+    pub fn set_depth(&mut self, depth: usize) {
+        self.ai.set_depth(depth);
+    }
+
+    /// Switch to monte carlo tree search, thinking for the given time per move.
+    pub fn set_time_budget(&mut self, budget: Duration) {
+        self.ai.set_time_budget(budget);
+    }
+
+    /// Create an ai with the settings of this player, to suggest columns to a player.
+    pub(crate) fn adviser(&self) -> Ai {
+        self.ai.adviser()
+    }
+
+    /// Play a game against another ai player on a board of `width` columns and `height`
+    /// playable rows, without display or input. This player goes first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidDim` if the board is smaller than 4x4.
+    pub fn play_against(
+        &mut self,
+        other: &mut AiPlayer,
+        width: usize,
+        height: usize,
+    ) -> GameResult<GameRecord> {
+        let mut board = Board::new(width, height + 1)?;
+        let mut tile = TileType::Player1;
+        loop {
+            let ai = match tile {
+                TileType::Player1 => &mut self.ai,
+                _ => &mut other.ai,
+            };
+            let col = ai.make_move(&board, tile);
+            board.place(col, tile)?;
+
+            let winner = match board.check_win() {
+                CheckWin::Player(_) => Some(tile),
+                CheckWin::Draw => None,
+                CheckWin::None => {
+                    tile = tile.opponent();
+                    continue;
+                }
+            };
+            return Ok(GameRecord {
+                winner,
+                moves: board.history().len(),
+            });
+        }
+    }
+}
+
+impl PlayerController for AiPlayer {
+    fn play(&mut self, turn: &mut Turn<'_, '_>) -> Action {
+        let tile = turn.tile();
+        Action::Place(self.ai.make_move(turn.board().inner(), tile))
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(format!("AI ({})", self.ai.level()))
+    }
+}
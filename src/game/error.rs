@@ -1,29 +1,119 @@
 /// All the possible recoverable errors produced by the game.
 #[derive(Debug)]
 pub enum Error {
+    /// The column is outside the board.
     InvalidColumn,
+    /// The tile can't be placed, for example because it is `TileType::Empty`.
     InvalidType,
+    /// The column has no empty spots left.
     ColumnFull,
+    /// The bottom tile of the column does not belong to the player popping it.
+    CantPop,
+    /// The input could not be understood. Contains the input.
     InvalidInput(String),
+    /// The board dimensions are not supported.
     InvalidDim,
+    /// Players can't be asked to connect this many tiles on the board. Contains the number.
+    InvalidConnect(usize),
+    /// The position could not be read. Contains the reason.
+    InvalidPosition(String),
+    /// The position has another size than the board, or is too small. Contains the number of
+    /// columns and playable rows of the position.
+    PositionSize(usize, usize),
+    /// A tile in the position has an empty spot below it. Contains the column, 1 indexed.
+    FloatingTile(usize),
+    /// The players can't have this many tiles when taking turns, with the player on move in the
+    /// position. Contains the number of tiles of player 1 and player 2.
+    TileCount(usize, usize),
+    /// There is no player to perform the action.
     NoPlayer,
+    /// There are no moves left to undo.
     NoUndos,
-    Display(c4_display::Error),
+    /// There are no undone moves left to redo.
+    NoRedos,
+    /// The animation could not be parsed. Contains the reason.
+    InvalidAnimation(String),
+    /// Reading or writing failed.
+    Io(std::io::Error),
+    /// The peer did not follow the network protocol. Contains the reason.
+    Protocol(String),
+    /// The saved game could not be read. Contains the reason.
+    Notation(String),
+    /// There is no game to save.
+    NoGame,
+    /// The player profiles could not be read. Contains the reason.
+    InvalidProfiles(String),
+    /// The config is not valid. Contains the reason.
+    InvalidConfig(String),
+    /// Reading the gpio pins failed.
+    #[cfg(feature = "gpio")]
+    Gpio(rppal::gpio::Error),
+    /// The led matrix display failed. Contains the error of the display driver.
+    Display(Box<dyn std::error::Error + Send + Sync>),
 }
 
 /// Result type making use of custom errors.
 pub(crate) type GameResult<T> = Result<T, Error>;
 
-impl From<c4_display::Error> for Error {
-    fn from(e: c4_display::Error) -> Self {
-        Self::Display(e)
+#[cfg(feature = "gpio")]
+impl From<rppal::gpio::Error> for Error {
+    fn from(e: rppal::gpio::Error) -> Self {
+        Self::Gpio(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
     }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self)
+        match self {
+            Error::InvalidColumn => write!(f, "column does not exist"),
+            Error::InvalidType => write!(f, "invalid tile type"),
+            Error::ColumnFull => write!(f, "column is full"),
+            Error::CantPop => write!(f, "bottom tile is not your own"),
+            Error::InvalidInput(input) => write!(f, "invalid input: {input}"),
+            Error::InvalidDim => write!(f, "unsupported board dimensions"),
+            Error::InvalidConnect(n) => write!(f, "can't connect {n} tiles on this board"),
+            Error::InvalidPosition(reason) => write!(f, "invalid position: {reason}"),
+            Error::PositionSize(width, height) => {
+                write!(f, "position of {width}x{height} doesn't fit the board")
+            }
+            Error::FloatingTile(col) => write!(f, "tile floating in column {col}"),
+            Error::TileCount(x, o) => {
+                write!(
+                    f,
+                    "impossible number of tiles for the player on move: {x} x and {o} o"
+                )
+            }
+            Error::NoPlayer => write!(f, "no player available"),
+            Error::NoUndos => write!(f, "no moves to undo"),
+            Error::NoRedos => write!(f, "no moves to redo"),
+            Error::InvalidAnimation(reason) => write!(f, "invalid animation: {reason}"),
+            Error::Io(e) => write!(f, "io error: {e}"),
+            Error::Protocol(reason) => write!(f, "protocol error: {reason}"),
+            Error::Notation(reason) => write!(f, "invalid game: {reason}"),
+            Error::NoGame => write!(f, "no game to save"),
+            Error::InvalidProfiles(reason) => write!(f, "invalid profiles: {reason}"),
+            Error::InvalidConfig(reason) => write!(f, "invalid config: {reason}"),
+            #[cfg(feature = "gpio")]
+            Error::Gpio(e) => write!(f, "gpio error: {e}"),
+            Error::Display(e) => write!(f, "display error: {e}"),
+        }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            #[cfg(feature = "gpio")]
+            Error::Gpio(e) => Some(e),
+            Error::Display(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
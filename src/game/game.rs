@@ -1,108 +1,162 @@
-use std::time::Duration;
-
-use c4_display::{
-    spin_wait, Animation, AnimationFrame, BlinkInfo, DisplayInterface, LedColor, LedState,
-    PinConfig, Rotation, Running, SyncType,
-};
+use std::{net::ToSocketAddrs, path::Path, time::Duration};
 
 use crate::{
-    ai::Ai,
+    ai::{Ai, AiLevel},
+    display::{Animation, AnimationFrame, Blink, Color, Led, MatrixDisplay, TerminalDisplay},
     game::{
         board::Board,
-        button::ButtonsAsync,
+        button::Buttons,
         components::{
-            ActivePlayer, AiState, ButtonState, Check4, DisplayState, Player, Players, TileType,
+            ActivePlayer, AiState, ButtonState, CheckWin, DisplayState, Line, Move, Played, Player,
+            Players, Rules, TileType,
         },
+        config::{Config, BOARD_SIZES},
+        controller::{Action, AiPlayer, LocalPlayer, PlayerController, Turn},
         error::{Error, GameResult},
         input::{InputHandler, InputValue},
+        network::{NetworkPlayer, DEFAULT_PORT},
+        notation::{Outcome, SavedGame},
+        profile::{Profiles, Record},
+        spectator::{Event, Spectators},
     },
 };
 
+/// Number of times the column suggested by a hint blinks.
+const HINT_BLINKS: u32 = 3;
+
 /// Game manager struct.
-pub struct Game<'g, const W: usize, const H: usize> {
-    board: Board<W, H>,
-    ai: Ai<W, H>,
+pub struct Game<'g> {
+    board: Board,
+    /// Ai used for analysis.
+    ai: Ai,
+    /// Ai suggesting columns, with the settings of the last ai opponent.
+    hint_ai: Ai,
     players: Players,
-    display: Option<DisplayInterface<'g, Running, W, H>>,
+    /// Controllers deciding the moves of player 1 and player 2.
+    controllers: [Option<Box<dyn PlayerController>>; 2],
+    display: Option<Box<dyn MatrixDisplay + 'g>>,
     input_handler: InputHandler,
     ai_state: AiState,
+    /// Difficulty level used when the ai is enabled.
+    ai_level: AiLevel,
     button_state: ButtonState,
     display_state: DisplayState,
+    spectators: Option<Spectators>,
+    /// The last round that was played, kept until the next round starts.
+    last_round: Option<SavedGame>,
+    /// Profiles keeping the standings of the players, [None] if results aren't kept.
+    profiles: Option<Profiles>,
+    config: Config,
+    /// Rules the game is played by.
+    rules: Rules,
+    /// Board size chosen from the menu, as (columns, playable rows).
+    requested_size: Option<(usize, usize)>,
 }
 
-impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
-    /// Create a new game manager instance.
+impl<'g> Game<'g> {
+    /// Create a new game manager instance, on the standard board of 7 columns and 6 rows.
     ///
     /// # Error
     ///
-    /// Returns an error if the height or width constants are smaller than 4.
+    /// Returns an error if the default config is not valid, see [`Game::with_config`].
     pub fn new() -> GameResult<Self> {
-        let board = Board::<W, H>::new()?;
+        Self::with_config(Config::default())
+    }
+
+    /// Create a new game manager instance using the settings of the config. The board has the
+    /// size of the config.
+    ///
+    /// # Error
+    ///
+    /// Returns `Error::InvalidConfig` if the config is not valid.
+    pub fn with_config(config: Config) -> GameResult<Self> {
+        config.validate()?;
+        // row 0 of the board is the status bar
+        let mut board = Board::new(config.width, config.height + 1)?;
+        board.set_connect(config.connect)?;
         Ok(Self {
-            ai: Ai::new()?,
+            ai: Ai::new(),
+            hint_ai: Ai::new(),
             board,
-            // TODO better player management system
             players: Players::new(
                 Player {
                     name: String::from("a"),
                     score: 0,
-                    color: LedColor::Red,
+                    color: config.colors[0],
                     tile: TileType::Player1,
                 },
                 Player {
                     name: String::from("b"),
                     score: 0,
-                    color: LedColor::Yellow,
+                    color: config.colors[1],
                     tile: TileType::Player2,
                 },
             ),
+            controllers: [Some(Box::new(LocalPlayer)), Some(Box::new(LocalPlayer))],
             display: None,
             ai_state: AiState::Disabled,
+            ai_level: AiLevel::default(),
             input_handler: InputHandler::new(),
             button_state: ButtonState::Disabled,
             display_state: DisplayState::Disabled,
+            spectators: None,
+            last_round: None,
+            profiles: None,
+            rules: config.rules,
+            config,
+            requested_size: None,
         })
     }
 
-    /// Enable led matrix display
-    pub fn enable_display(&mut self) {
-        self.display = Some(DisplayInterface::new("connect4").start(
-            60.0,
-            PinConfig {
-                sr_serin: 17,
-                sr_srclk: 22,
-                sr_rclk: 23,
-                sr_srclr: 24,
-                sr_oe: 27,
-                dec_a0: 25,
-                dec_a1: 11,
-                dec_a2: 5,
-                dec_le: 6,
-                dec_e1: 10,
-            },
-        ));
+    /// Get the settings the game was created with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Enable colored display in the terminal, for playing without the led matrix
+    pub fn enable_terminal_display(&mut self) {
+        self.set_display(Box::new(TerminalDisplay::new()));
+    }
+
+    /// Show the game on the given display, replacing the current one.
+    pub fn set_display(&mut self, display: Box<dyn MatrixDisplay + 'g>) {
+        self.disable_display();
+        self.display = Some(display);
         self.display_state = DisplayState::Enabled;
+        self.update_disp();
     }
 
-    /// Disable led matrix display
+    /// Disable the display
     pub fn disable_display(&mut self) {
-        let disp = self.display.take();
-
-        if disp.is_some() {
-            disp.unwrap().stop();
+        if let Some(mut disp) = self.display.take() {
+            drop(disp.stop()); // drop to discard result without warning
         }
+        self.display_state = DisplayState::Disabled;
     }
 
     /// Enable push buttons on the matrix board
+    #[cfg(feature = "gpio")]
     pub fn enable_buttons(&mut self) -> GameResult<()> {
         if self.button_state == ButtonState::Disabled {
-            self.input_handler.start_buttons(2, 4, 3, 15, 14)?;
-            // self.buttons = Some(ButtonsAsync::new(2, 4, 3, 15, 14)?);
-            self.button_state = ButtonState::Enabled;
+            let pins = self.config.button_pins;
+            self.set_buttons(Buttons::gpio(
+                pins.up,
+                pins.down,
+                pins.left,
+                pins.right,
+                pins.center,
+            )?);
         }
         Ok(())
     }
 
+    /// Use the given buttons, replacing the current ones.
+    pub fn set_buttons(&mut self, buttons: Buttons) {
+        self.disable_buttons();
+        self.input_handler.start_buttons(buttons);
+        self.button_state = ButtonState::Enabled;
+    }
+
     /// Disable push buttons on the matrix board
     pub fn disable_buttons(&mut self) {
         if self.button_state == ButtonState::Enabled {
@@ -112,44 +166,707 @@ impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
         }
     }
 
-    /// Enable the ai opponent.
+    /// Play without display or buttons, using every line of stdin in order.
+    ///
+    /// Meant for piping commands and moves in, like `printf 'y\n4\n4\n' | connect4 --headless`.
+    /// The game stops at the end of the input.
+    pub fn enable_headless(&mut self) {
+        self.disable_display();
+        self.disable_buttons();
+        self.input_handler.set_ordered(true);
+    }
+
+    /// Get the rules the game is played by.
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// Play the next rounds by the given rules.
+    ///
+    /// The ai only drops tiles, also when playing by the PopOut rules.
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+    }
+
+    /// Get the number of tiles a player has to connect to win.
+    pub fn connect(&self) -> usize {
+        self.board.connect()
+    }
+
+    /// Let players connect the given number of tiles to win the next rounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidConnect` if the line is shorter than 3, or doesn't fit on the
+    /// board.
+    pub fn set_connect(&mut self, connect: usize) -> GameResult<()> {
+        self.board.set_connect(connect)
+    }
+
+    /// Let the player with the given tile make the first move of the next round.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidType` if the tile is of type `TileType::Empty`.
+    pub fn set_starting_player(&mut self, tile: TileType) -> GameResult<()> {
+        self.players.set_active(match tile {
+            TileType::Player1 => ActivePlayer::Player1,
+            TileType::Player2 => ActivePlayer::Player2,
+            TileType::Empty => return Err(Error::InvalidType),
+        });
+        self.board.set_active(tile);
+        // a loaded game keeps the player who moved first
+        if self.board.history().is_empty() {
+            self.board.set_start(tile);
+        }
+        Ok(())
+    }
+
+    /// Enable the ai opponent as player 2.
     pub fn enable_ai(&mut self) {
+        self.enable_ai_with(self.ai_level);
+    }
+
+    /// Enable the ai opponent as player 2, at the given difficulty level.
+    pub fn enable_ai_with(&mut self, level: AiLevel) {
+        let ai = AiPlayer::new(level);
+        self.ai_level = level;
+        self.set_ai(ai);
+    }
+
+    /// Enable the monte carlo tree search ai opponent as player 2, thinking for the given time
+    /// per move.
+    pub fn enable_ai_with_time_budget(&mut self, budget: Duration) {
+        let mut ai = AiPlayer::new(AiLevel::Mcts);
+        ai.set_time_budget(budget);
+        self.ai_level = AiLevel::Mcts;
+        self.set_ai(ai);
+    }
+
+    /// Enable the ai opponent as player 2, searching the given number of moves ahead.
+    ///
+    /// A depth of 0 makes the ai only look at the next move.
+    pub fn enable_ai_with_depth(&mut self, depth: usize) {
+        let mut ai = AiPlayer::new(self.ai_level);
+        ai.set_depth(depth);
+        self.set_ai(ai);
+    }
+
+    fn set_ai(&mut self, ai: AiPlayer) {
+        self.hint_ai = ai.adviser();
+        self.controllers[Self::seat(TileType::Player2)] = Some(Box::new(ai));
         self.ai_state = AiState::Enabled;
     }
 
-    /// Disable the ai opponent.
+    /// Disable the ai opponent, letting a local player take over player 2.
     pub fn disable_ai(&mut self) {
+        self.controllers[Self::seat(TileType::Player2)] = Some(Box::new(LocalPlayer));
         self.ai_state = AiState::Disabled;
     }
 
-    // ! win_pos should be removed once the animation system is implemented, and be done instead via a disp.add_animation() method (or similar) in start_round()
-    // fn update_disp(&mut self, win_pos: Option<[(usize, usize); 4]>) {
+    /// Let the controller decide the moves of the player with the given tile.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidType` if the tile is of type `TileType::Empty`.
+    pub fn set_controller(
+        &mut self,
+        tile: TileType,
+        controller: Box<dyn PlayerController>,
+    ) -> GameResult<()> {
+        if tile == TileType::Empty {
+            return Err(Error::InvalidType);
+        }
+        if tile == TileType::Player2 {
+            self.ai_state = AiState::Disabled;
+        }
+        self.controllers[Self::seat(tile)] = Some(controller);
+        Ok(())
+    }
+
+    /// Keep the standings of the players in the profiles stored in the given file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read, see [`Profiles::open`].
+    pub fn enable_profiles(&mut self, path: impl AsRef<Path>) -> GameResult<()> {
+        self.profiles = Some(Profiles::open(path)?);
+        Ok(())
+    }
+
+    /// Stop keeping the standings of the players.
+    pub fn disable_profiles(&mut self) {
+        self.profiles = None;
+    }
+
+    /// Get the profiles of the players, [None] if results aren't kept.
+    pub fn profiles(&self) -> Option<&Profiles> {
+        self.profiles.as_ref()
+    }
+
+    /// Let the player with the given tile play under the given name.
+    ///
+    /// When profiles are enabled the profile of the player is created if it doesn't exist
+    /// yet, and the player gets their preferred color unless the opponent already uses it.
+    /// The scores of the current session start over.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidType` if the tile is of type `TileType::Empty`.
+    ///
+    /// Returns `Error::InvalidInput` if the name is empty.
+    ///
+    /// Returns `Error::Io` if the profiles can't be saved.
+    pub fn set_player(&mut self, tile: TileType, name: &str) -> GameResult<()> {
+        if tile == TileType::Empty {
+            return Err(Error::InvalidType);
+        }
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(Error::InvalidInput(String::from("empty player name")));
+        }
+
+        let mut color = self.players.get(tile).color;
+        if let Some(profiles) = &mut self.profiles {
+            let profile = profiles.get_or_create(name, color)?;
+            if profile.color != self.players.get(tile.opponent()).color {
+                color = profile.color;
+            }
+            profiles.save()?;
+        }
+
+        let player = self.players.get_mut(tile);
+        player.name = name.to_string();
+        player.color = color;
+        self.players.reset_scores();
+        self.update_disp();
+        Ok(())
+    }
+
+    /// Change the color of the player with the given tile, remembering it in their profile.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidType` if the tile is of type `TileType::Empty`.
+    ///
+    /// Returns `Error::InvalidInput` if the color is off or used by the opponent.
+    ///
+    /// Returns `Error::Io` if the profiles can't be saved.
+    pub fn set_color(&mut self, tile: TileType, color: Color) -> GameResult<()> {
+        if tile == TileType::Empty {
+            return Err(Error::InvalidType);
+        }
+        if color == Color::Off {
+            return Err(Error::InvalidInput(String::from(
+                "players need a visible color",
+            )));
+        }
+        let opponent = self.players.get(tile.opponent());
+        if opponent.color == color {
+            return Err(Error::InvalidInput(format!(
+                "{color} is already used by {}",
+                opponent.name
+            )));
+        }
+
+        let player = self.players.get_mut(tile);
+        player.color = color;
+        let name = player.name.clone();
+        if let Some(profiles) = &mut self.profiles {
+            if profiles.get(&name).is_some() {
+                profiles.get_or_create(&name, color)?.color = color;
+                profiles.save()?;
+            }
+        }
+        self.update_disp();
+        Ok(())
+    }
+
+    /// Add the result of the round to the profiles of the players at the table.
+    ///
+    /// `winner` is [None] if the round was a draw.
+    fn record_result(&mut self, winner: Option<TileType>) {
+        // only the players at the table keep their standings here
+        let results = [TileType::Player1, TileType::Player2]
+            .into_iter()
+            .filter(|&tile| {
+                self.controllers[Self::seat(tile)]
+                    .as_ref()
+                    .is_some_and(|controller| controller.is_local())
+            })
+            .map(|tile| {
+                let result = match winner {
+                    Some(winner) if winner == tile => Record::WIN,
+                    Some(_) => Record::LOSS,
+                    None => Record::DRAW,
+                };
+                (self.name(tile), self.name(tile.opponent()), result)
+            })
+            .collect::<Vec<(String, String, Record)>>();
+
+        let Some(profiles) = &mut self.profiles else {
+            return;
+        };
+        let mut changed = false;
+        for (name, opponent, result) in results {
+            changed |= profiles.add_result(&name, &opponent, result);
+        }
+        if changed {
+            if let Err(err) = profiles.save() {
+                log::error!("Could not save the player profiles: {err}");
+            }
+        }
+    }
+
+    /// Print the standings, or the record of the given player against every opponent.
+    fn print_leaderboard(&self, name: Option<String>) {
+        let Some(profiles) = &self.profiles else {
+            println!("Player profiles are not enabled");
+            return;
+        };
+
+        if let Some(name) = name {
+            match profiles.get(&name) {
+                Some(profile) => {
+                    println!("{name}: {}", profile.total());
+                    for (opponent, record) in &profile.opponents {
+                        println!("  vs {opponent}: {record}");
+                    }
+                }
+                None => println!("{name} has no profile"),
+            }
+            return;
+        }
+
+        let standings = profiles.leaderboard();
+        if standings.is_empty() {
+            println!("No games have been played yet");
+            return;
+        }
+        println!(
+            "  #  {:<16}{:>7}{:>5}{:>5}{:>5}",
+            "name", "played", "W", "L", "D"
+        );
+        for (i, (name, record)) in standings.iter().enumerate() {
+            println!(
+                "{:>3}  {name:<16}{:>7}{:>5}{:>5}{:>5}",
+                i + 1,
+                record.played(),
+                record.wins,
+                record.losses,
+                record.draws
+            );
+        }
+    }
+
+    /// Stream the game to spectators connecting to the given address.
+    ///
+    /// Every move, win, draw and score change is sent as a line of JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the address can't be bound.
+    pub fn enable_spectators(&mut self, addr: impl ToSocketAddrs) -> GameResult<()> {
+        self.spectators = Some(Spectators::new(addr)?);
+        Ok(())
+    }
+
+    /// Stop streaming the game to spectators, disconnecting all of them.
+    pub fn disable_spectators(&mut self) {
+        self.spectators = None;
+    }
+
+    /// Send an event to the spectators.
+    fn spectate(&self, event: Event) {
+        if let Some(spectators) = &self.spectators {
+            spectators.send(&event);
+        }
+    }
+
+    /// Send the last move on the board to the spectators.
+    fn spectate_move(&self) {
+        if let Some(&played) = self.board.history().last() {
+            self.spectate_played(played);
+        }
+    }
+
+    /// Send a move on the board to the spectators.
+    fn spectate_played(&self, played: Played) {
+        let name = self.name(played.tile);
+        self.spectate(match played.pop {
+            true => Event::Pop {
+                tile: played.tile,
+                name,
+                col: played.x + 1,
+            },
+            false => Event::Move {
+                tile: played.tile,
+                name,
+                col: played.x + 1,
+                row: self.board.height() - played.y,
+            },
+        });
+    }
+
+    /// Get the tile of the player who connected the given lines.
+    fn winner(&self, lines: &[Line]) -> TileType {
+        let (y, x) = lines[0][0];
+        self.board.get(x, y).unwrap_or_default()
+    }
+
+    /// Send the scores to the spectators.
+    fn spectate_score(&self) {
+        self.spectate(Event::Score {
+            scores: [
+                (self.name(TileType::Player1), self.players.player1.score),
+                (self.name(TileType::Player2), self.players.player2.score),
+            ],
+        });
+    }
+
+    /// Write down the game being played, or the last round if no game is being played.
+    fn record(&self) -> Option<SavedGame> {
+        if self.board.history().is_empty() {
+            return self.last_round.clone();
+        }
+        let result = match self.board.check_win() {
+            CheckWin::Player(cells) => Outcome::Won(self.winner(&cells)),
+            CheckWin::Draw => Outcome::Draw,
+            CheckWin::None => Outcome::Ongoing,
+        };
+        let (width, height) = self.size();
+        Some(SavedGame {
+            width,
+            height,
+            connect: self.board.connect(),
+            rules: self.rules,
+            position: self.board.start_position(),
+            first: self.board.history()[0].tile,
+            players: [
+                (self.name(TileType::Player1), self.players.player1.color),
+                (self.name(TileType::Player2), self.players.player2.color),
+            ],
+            moves: self
+                .board
+                .history()
+                .iter()
+                .map(|played| played.to_move())
+                .collect(),
+            result,
+        })
+    }
+
+    /// Save the game being played to a file, or the last round if no game is being played.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::NoGame` if no game has been played yet.
+    ///
+    /// Returns `Error::Notation` if the board has more than 9 columns, as columns are written
+    /// as single digits.
+    ///
+    /// Returns `Error::Io` if the file can't be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> GameResult<()> {
+        if self.board.width() > 9 {
+            return Err(Error::Notation(String::from(
+                "boards with more than 9 columns can't be saved",
+            )));
+        }
+        self.record().ok_or(Error::NoGame)?.to_file(path)
+    }
+
+    /// Load a saved game, to continue it in the next round. The game is continued by the rules
+    /// it was played by.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file can't be read.
+    ///
+    /// Returns `Error::Notation` if the file is not a valid game, is played on a board of
+    /// another size, starts from an invalid position, or if any of its moves can't be played.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> GameResult<()> {
+        let game = SavedGame::from_file(path)?;
+        let (width, height) = self.size();
+        if (game.width, game.height) != (width, height) {
+            return Err(Error::Notation(format!(
+                "game is played on a {}x{} board, not {width}x{height}",
+                game.width, game.height,
+            )));
+        }
+
+        let mut board = match &game.position {
+            Some(position) => position
+                .parse::<Board>()
+                .and_then(|board| self.check_size(board))
+                .map_err(|err| Error::Notation(err.to_string()))?,
+            None => Board::new(width, height + 1)?,
+        };
+        board
+            .set_connect(game.connect)
+            .map_err(|err| Error::Notation(err.to_string()))?;
+        board.set_start(game.first);
+        let mut tile = game.first;
+        for (i, &mv) in game.moves.iter().enumerate() {
+            if !matches!(board.check_win(), CheckWin::None) {
+                return Err(Error::Notation(format!(
+                    "move {}: game is already over",
+                    i + 1
+                )));
+            }
+            match mv {
+                Move::Place(col) => board.place(col, tile),
+                Move::Pop(col) if game.rules == Rules::PopOut => board.pop(col, tile),
+                Move::Pop(_) => {
+                    return Err(Error::Notation(format!(
+                        "move {}: pops are not allowed by the {} rules",
+                        i + 1,
+                        game.rules
+                    )))
+                }
+            }
+            .map_err(|err| Error::Notation(format!("move {}: {err}", i + 1)))?;
+            tile = tile.opponent();
+        }
+
+        let [(name1, color1), (name2, color2)] = game.players;
+        self.players.player1.name = name1;
+        self.players.player1.color = color1;
+        self.players.player2.name = name2;
+        self.players.player2.color = color2;
+        self.players.set_active(match tile {
+            TileType::Player2 => ActivePlayer::Player2,
+            _ => ActivePlayer::Player1,
+        });
+        self.rules = game.rules;
+        self.board = board;
+        self.board.set_active(tile);
+        self.last_round = None;
+        self.update_disp();
+        Ok(())
+    }
+
+    /// Set up the board from a position, to continue from it in the next round.
+    ///
+    /// The rows of the position are written from the top down and separated by `/`, using `x`
+    /// for tiles of player 1, `o` for tiles of player 2 and `.` for empty spots. After a space
+    /// follows the player on move, like `......./......./......./......./...o.../..xxo.. x`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidPosition` if the position can't be read, or is already decided.
+    ///
+    /// Returns `Error::PositionSize` if the position is of another size than the board.
+    ///
+    /// Returns `Error::FloatingTile` if a tile has an empty spot below it.
+    ///
+    /// Returns `Error::TileCount` if the players can't have this many tiles, with the player on
+    /// move of the position.
+    pub fn load_position(&mut self, position: &str) -> GameResult<()> {
+        let mut board = self.check_size(position.parse::<Board>()?)?;
+        board.set_connect(self.connect())?;
+        if !matches!(board.check_win(), CheckWin::None) {
+            return Err(Error::InvalidPosition(String::from(
+                "the game is already over",
+            )));
+        }
+
+        let tile = board.to_move();
+        self.players.set_active(match tile {
+            TileType::Player2 => ActivePlayer::Player2,
+            _ => ActivePlayer::Player1,
+        });
+        self.board = board;
+        self.board.set_active(tile);
+        self.last_round = None;
+        self.update_disp();
+        Ok(())
+    }
+
+    /// Wait for a game on another board to join, and play against it as player 1.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection fails, see [`NetworkPlayer::host`].
+    pub fn host(&mut self, addr: impl ToSocketAddrs) -> GameResult<()> {
+        let peer = NetworkPlayer::host(
+            addr,
+            &self.players.player1.name,
+            self.size(),
+            self.board.connect(),
+            self.rules,
+        )?;
+        println!("{} joined the game", peer.name().unwrap_or_default());
+        self.set_controller(TileType::Player1, Box::new(LocalPlayer))?;
+        self.set_controller(TileType::Player2, Box::new(peer))?;
+        self.players.set_active(ActivePlayer::Player1);
+        Ok(())
+    }
+
+    /// Join a game hosted on another board, and play against it as player 2.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection fails, see [`NetworkPlayer::connect`].
+    pub fn join(&mut self, addr: impl ToSocketAddrs) -> GameResult<()> {
+        let peer = NetworkPlayer::connect(
+            addr,
+            &self.players.player2.name,
+            self.size(),
+            self.board.connect(),
+            self.rules,
+        )?;
+        println!("Joined the game of {}", peer.name().unwrap_or_default());
+        self.set_controller(TileType::Player1, Box::new(peer))?;
+        self.set_controller(TileType::Player2, Box::new(LocalPlayer))?;
+        self.players.set_active(ActivePlayer::Player1);
+        Ok(())
+    }
+
+    /// Index of the seat of the player with the given tile.
+    fn seat(tile: TileType) -> usize {
+        match tile {
+            TileType::Player2 => 1,
+            _ => 0,
+        }
+    }
+
+    /// Name of the player with the given tile.
+    fn name(&self, tile: TileType) -> String {
+        let player = match tile {
+            TileType::Player2 => &self.players.player2,
+            _ => &self.players.player1,
+        };
+        self.controllers[Self::seat(tile)]
+            .as_ref()
+            .and_then(|controller| controller.name())
+            .unwrap_or_else(|| player.name.clone())
+    }
+
+    /// If one of the players is playing along on another board.
+    fn has_remote(&self) -> bool {
+        self.controllers
+            .iter()
+            .flatten()
+            .any(|controller| controller.is_remote())
+    }
+
+    /// If the player on turn is sitting at the table.
+    fn active_is_local(&self) -> bool {
+        self.controllers[Self::seat(self.players.active().tile)]
+            .as_ref()
+            .is_some_and(|controller| controller.is_local())
+    }
+
+    pub(super) fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Get the size of the board, as (columns, playable rows).
+    fn size(&self) -> (usize, usize) {
+        (self.board.width(), self.board.height() - 1)
+    }
+
+    /// Check if a board read from a position has the size of the board of the game.
+    ///
+    /// Returns `Error::PositionSize` with the size of the position if it doesn't.
+    fn check_size(&self, board: Board) -> GameResult<Board> {
+        match (board.width(), board.height() - 1) {
+            size if size == self.size() => Ok(board),
+            (width, height) => Err(Error::PositionSize(width, height)),
+        }
+    }
+
+    pub(super) fn active_tile(&self) -> TileType {
+        self.players.active().tile
+    }
+
+    /// Let the local player on turn decide what to do, using the buttons or the terminal.
+    pub(super) fn local_turn(&mut self) -> Action {
+        loop {
+            match self.input_handler.get() {
+                Ok(val) => match val {
+                    InputValue::BtnLeft => {
+                        self.board.selected_left();
+                        self.update_disp();
+                    }
+                    InputValue::BtnRight => {
+                        self.board.selected_right();
+                        self.update_disp();
+                    }
+                    InputValue::BtnCenter => return Action::Place(self.board.selected() + 1),
+                    InputValue::BtnHoldDown => return Action::Pop(self.board.selected() + 1),
+                    InputValue::Col(col) => return Action::Place(col),
+                    InputValue::Pop(col) => return Action::Pop(col),
+                    InputValue::Undo => return Action::Undo,
+                    InputValue::Redo => return Action::Redo,
+                    InputValue::Analyse => self.analyse(),
+                    InputValue::Hint | InputValue::BtnUp => self.hint(),
+                    InputValue::Save(path) => match self.save(&path) {
+                        Ok(()) => println!("Saved the game to {path}"),
+                        Err(err) => println!("Could not save the game: {err}"),
+                    },
+                    InputValue::Position(None) => println!("{}", self.board.position()),
+                    InputValue::Help => {
+                        print!(
+                            "Place a piece in a column by typing a number between 1 and {}",
+                            self.board.width()
+                        );
+                        println!(" (the column numbers are visible above the columns)");
+                        if self.rules == Rules::PopOut {
+                            println!("Pop your own piece out of the bottom of a column by typing p and the column, like p3");
+                        }
+                        println!("Type undo or redo to take back or replay a move");
+                        println!("Type analyse to see the outcome of every column");
+                        println!("Type hint or press the up button to ask the ai for a column");
+                        println!("Type save <file> to save the game");
+                        println!("Type position to write down the position");
+                        println!("Type quit to stop the round");
+                    }
+                    InputValue::Quit => return Action::Quit,
+                    _ => (),
+                },
+                Err(err) => match err {
+                    Error::InvalidInput(str) => println!(
+                        "Invalid input: {str}\nInput must be a number between 1 and {}",
+                        self.board.width()
+                    ),
+                    _ => panic!("expected Error::InvalidInput, found {err}"),
+                },
+            }
+        }
+    }
+
     fn update_disp(&mut self) {
         if self.display_state == DisplayState::Enabled {
-            let mut temp = vec![vec![LedState::default(); W]; H];
+            let mut temp = vec![vec![Led::default(); self.board.width()]; self.board.height()];
 
-            for y in 0..H {
-                for x in 0..W {
+            for (y, row) in temp.iter_mut().enumerate() {
+                for (x, led) in row.iter_mut().enumerate() {
                     match self.board.get(x, y) {
-                        Ok(TileType::Player1) => temp[y][x].color = self.players.player1.color,
-                        Ok(TileType::Player2) => temp[y][x].color = self.players.player2.color,
-                        Ok(TileType::Empty) => temp[y][x] = LedState::default(),
+                        Ok(TileType::Player1) => led.color = self.players.player1.color,
+                        Ok(TileType::Player2) => led.color = self.players.player2.color,
+                        Ok(TileType::Empty) => *led = Led::default(),
                         Err(_) => (),
                     }
                 }
             }
 
-            self.display
-                .as_mut()
-                .unwrap()
-                .sync(SyncType::All(temp))
-                .unwrap();
+            self.display.as_mut().unwrap().sync(temp).unwrap();
+        }
+    }
+
+    /// If the board is shown in the terminal by the display, so it doesn't need printing.
+    fn display_in_terminal(&self) -> bool {
+        self.display.as_ref().is_some_and(|disp| disp.in_terminal())
+    }
 
-            // self.display
-            //     .as_mut()
-            //     .unwrap()
-            //     .sync(SyncType::Rotate(Rotation::Clockwise))
-            //     .unwrap();
+    /// Play the idle animation, shown while no round is being played.
+    fn idle_ani(&mut self) {
+        if let Some(disp) = &mut self.display {
+            match Animation::from_file(&self.config.idle_animation) {
+                Ok(ani) => disp.add_animation(ani).unwrap(),
+                Err(err) => log::warn!("Could not load idle animation: {err}"),
+            }
         }
     }
 
@@ -159,96 +876,148 @@ impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
             self.display.as_mut().unwrap().clear_animations();
         }
 
-        self.board.set_active(self.players.active().tile);
+        let tile = self.players.active().tile;
+        self.board.set_active(tile);
+        // a loaded game continues with the player who moved first
+        if self.board.history().is_empty() {
+            self.board.set_start(tile);
+        }
         self.update_disp();
+        let (width, height) = self.size();
+        self.spectate(Event::Round {
+            width,
+            height,
+            connect: self.board.connect(),
+            rules: self.rules,
+            position: self.board.start_position(),
+            players: [self.name(TileType::Player1), self.name(TileType::Player2)],
+        });
+        // a loaded game continues where it was left
+        for &played in self.board.history() {
+            self.spectate_played(played);
+        }
+
+        loop {
+            let tile = self.players.active().tile;
+            if !self.display_in_terminal() {
+                print!("{}", self.board);
+            }
+            println!("{}'s turn.", self.name(tile));
 
-        'main: loop {
-            print!("{}", self.board);
-            println!("{}'s turn.", self.players.active().name);
-
-            match self.players.active {
-                ActivePlayer::Player1 | ActivePlayer::Player2 => loop {
-                    match self.input_handler.get() {
-                        Ok(val) => match val {
-                            InputValue::BtnLeft => {
-                                self.board.selected_left();
-                                self.update_disp();
-                            }
-                            InputValue::BtnRight => {
-                                self.board.selected_right();
-                                self.update_disp();
-                            }
-                            InputValue::BtnCenter => match self.board.place_selected() {
-                                Ok(()) => {
-                                    self.drop_ani(self.board.selected());
-                                    break;
-                                }
-                                Err(_) => (),
-                            },
-                            InputValue::Col(col) => {
-                                match self.board.place(col, self.players.active().tile) {
-                                    Ok(()) => {
-                                        self.drop_ani(col - 1);
-                                        break;
-                                    }
-                                    Err(Error::ColumnFull) => {
-                                        println!("Column {col} is already full!")
-                                    }
-                                    Err(Error::InvalidColumn) => {
-                                        println!("Column {col} does not exist!")
-                                    }
-                                    Err(Error::InvalidType) => {
-                                        println!(
-                                            "Cant place tile of type {:?}",
-                                            self.players.active().tile
-                                        )
-                                    }
-                                    _ => unimplemented!(),
-                                }
-                            }
-                            InputValue::Help => {
-                                print!("Place a piece in a column by typing a number between 1 and {W}");
-                                println!(" (the column numbers are visible above the columns)");
-                                println!("Type quit to stop the round");
-                            }
-                            InputValue::Quit => break 'main,
-                            _ => (),
-                        },
-                        Err(err) => match err {
-                            Error::InvalidInput(str) => println!(
-                                "Invalid input: {str}\nInput must be a number between 1 and {W}"
-                            ),
-                            _ => panic!("expected Error::InvalidInput, found {err}"),
-                        },
+            let mut controller = self.controllers[Self::seat(tile)]
+                .take()
+                .expect("every seat should have a controller");
+            let action = controller.play(&mut Turn::new(self));
+            let local = controller.is_local();
+            self.controllers[Self::seat(tile)] = Some(controller);
+
+            match action {
+                Action::Place(col) => match self.board.place(col, tile) {
+                    Ok(()) => {
+                        if let Some(opponent) = &mut self.controllers[Self::seat(tile.opponent())] {
+                            opponent.opponent_moved(col, tile);
+                        }
+                        self.spectate_move();
+                        self.drop_ani(col - 1);
+                        if !local {
+                            println!("{} placed in column {col}", self.name(tile));
+                        }
+                    }
+                    Err(Error::ColumnFull) => {
+                        println!("Column {col} is already full!");
+                        continue;
+                    }
+                    Err(Error::InvalidColumn) => {
+                        println!("Column {col} does not exist!");
+                        continue;
+                    }
+                    Err(Error::InvalidType) => {
+                        println!("Cant place tile of type {tile:?}");
+                        continue;
+                    }
+                    Err(e) => {
+                        println!("Can't place in column {col}: {e}");
+                        continue;
+                    }
+                },
+                Action::Pop(_) if self.rules != Rules::PopOut => {
+                    println!("Pieces can only be popped out when playing by the PopOut rules!");
+                    continue;
+                }
+                Action::Pop(col) => match self.board.pop(col, tile) {
+                    Ok(()) => {
+                        if let Some(opponent) = &mut self.controllers[Self::seat(tile.opponent())] {
+                            opponent.opponent_popped(col, tile);
+                        }
+                        self.spectate_move();
+                        if !local {
+                            println!("{} popped out of column {col}", self.name(tile));
+                        }
+                    }
+                    Err(Error::CantPop) => {
+                        println!("The bottom piece of column {col} is not yours!");
+                        continue;
+                    }
+                    Err(Error::InvalidColumn) => {
+                        println!("Column {col} does not exist!");
+                        continue;
+                    }
+                    Err(Error::InvalidType) => {
+                        println!("Cant pop tile of type {tile:?}");
+                        continue;
+                    }
+                    Err(e) => {
+                        println!("Can't pop out of column {col}: {e}");
+                        continue;
                     }
                 },
-                ActivePlayer::Ai => {
-                    print!("{}", self.board);
-                    let ai_move = self.ai.make_move(&self.board);
-                    self.board
-                        .place(ai_move, TileType::Player2)
-                        .expect("Ai move invalid");
-                    self.drop_ani(ai_move - 1);
-                    println!("AI placed in column {ai_move}");
+                Action::Undo | Action::Redo if self.has_remote() => {
+                    println!("Moves can't be taken back when playing against a remote player!");
+                    continue;
+                }
+                Action::Undo => {
+                    if self.undo().is_err() {
+                        println!("Nothing to undo!");
+                    }
+                    continue;
+                }
+                Action::Redo => {
+                    if self.redo().is_err() {
+                        println!("Nothing to redo!");
+                    }
+                    continue;
+                }
+                Action::Quit => {
+                    if let Some(opponent) = &mut self.controllers[Self::seat(tile.opponent())] {
+                        opponent.opponent_quit();
+                    }
+                    break;
                 }
             }
 
-            match self.board.check4() {
-                Check4::Player(pos) => {
+            match self.board.check_win() {
+                CheckWin::Player(lines) => {
+                    // after a pop the opponent can be the one connecting a line
+                    let winner = self.winner(&lines);
                     self.update_disp();
                     if self.display_state == DisplayState::Enabled {
-                        let mut leds = Vec::with_capacity(4);
+                        // lines crossing each other share a tile
+                        let mut pos = lines.concat();
+                        pos.sort();
+                        pos.dedup();
+
+                        let mut leds = Vec::with_capacity(pos.len());
                         for led in pos {
-                            let state = LedState {
-                                color: self.players.active().color,
-                                blink: Some(BlinkInfo {
-                                    dur: Duration::from_millis(250),
-                                    int: Duration::from_millis(500),
+                            let state = Led {
+                                color: self.players.get(winner).color,
+                                blink: Some(Blink {
+                                    dur: self.config.win_blink,
+                                    int: self.config.win_blink_interval,
                                 }),
                             };
                             leds.push((led.1, led.0, state));
                         }
-                        let frame = AnimationFrame::new(Duration::from_millis(500), leds, true);
+                        let frame = AnimationFrame::new(self.config.win_blink_interval, leds, true);
                         let animation = Animation::new(true, vec![frame], 0, false);
 
                         self.display
@@ -258,21 +1027,38 @@ impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
                             .unwrap();
                     }
 
-                    print!("{}", self.board);
-                    self.players.scored();
-                    println!("{} wins", self.players.active().name);
+                    if !self.display_in_terminal() {
+                        print!("{}", self.board);
+                    }
+                    self.players.scored(winner);
+                    self.record_result(Some(winner));
+                    match lines.len() {
+                        1 => println!("{} wins", self.name(winner)),
+                        n => println!("{} wins, connecting {n} lines at once", self.name(winner)),
+                    }
+                    let h = self.board.height();
+                    self.spectate(Event::Win {
+                        tile: winner,
+                        name: self.name(winner),
+                        lines: lines
+                            .iter()
+                            .map(|line| line.iter().map(|&(y, x)| (x + 1, h - y)).collect())
+                            .collect(),
+                    });
+                    self.spectate_score();
                     break;
                 }
-                Check4::Draw => {
-                    print!("{}", self.board);
+                CheckWin::Draw => {
+                    if !self.display_in_terminal() {
+                        print!("{}", self.board);
+                    }
                     println!("Draw");
+                    self.record_result(None);
+                    self.spectate(Event::Draw);
                     break;
                 }
-                Check4::None => {
-                    match self.ai_state {
-                        AiState::Enabled => self.players.swap_ai(),
-                        AiState::Disabled => self.players.swap(),
-                    }
+                CheckWin::None => {
+                    self.players.swap();
                     self.board.set_active(self.players.active().tile);
                 }
             }
@@ -280,20 +1066,23 @@ impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
             self.update_disp();
         }
 
-        match self.ai_state {
-            AiState::Enabled => self.players.set_active(ActivePlayer::Player1),
-            AiState::Disabled => {
-                self.players.swap();
-                self.board.set_active(self.players.active().tile);
-                println!(
-                    "\n{}'s score: {}\t{}'s score: {}",
-                    self.players.player1.name,
-                    self.players.player1.score,
-                    self.players.player2.name,
-                    self.players.player2.score,
-                );
-            }
+        self.last_round = self.record();
+
+        // the other player starts the next round
+        if let Some(first) = self.board.history().first() {
+            self.players.set_active(match first.tile {
+                TileType::Player1 => ActivePlayer::Player2,
+                _ => ActivePlayer::Player1,
+            });
         }
+        self.board.set_active(self.players.active().tile);
+        println!(
+            "\n{}'s score: {}\t{}'s score: {}",
+            self.name(TileType::Player1),
+            self.players.player1.score,
+            self.name(TileType::Player2),
+            self.players.player2.score,
+        );
 
         self.board.reset();
 
@@ -309,18 +1098,123 @@ impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
 
             self.display.as_mut().unwrap().clear_animations();
             self.update_disp();
+            self.idle_ani();
+        }
+    }
+
+    /// Print the outcome of placing in each column for the active player, assuming perfect play.
+    fn analyse(&mut self) {
+        if self.rules != Rules::Standard {
+            println!("Analysis is only available when playing by the standard rules");
+            return;
+        }
+        let outcomes = match self.ai.analyse(&self.board, self.players.active().tile) {
+            Some(outcomes) => outcomes,
+            None => {
+                println!("Analysis is only available on the standard 7x6 board, connecting 4");
+                return;
+            }
+        };
+
+        if let Some(outcome) = self.ai.solve(&self.board, self.players.active().tile) {
+            println!(
+                "With perfect play this is a {outcome} for {}",
+                self.players.active()
+            );
+        }
+        for (x, outcome) in outcomes.iter().enumerate() {
+            match outcome {
+                Some(outcome) => println!("  column {}: {outcome}", x + 1),
+                None if self.board.first_empty(x).is_err() => println!("  column {}: full", x + 1),
+                None => println!("  column {}: unknown", x + 1),
+            }
+        }
+    }
+
+    /// Ask the ai for the best column for the active player, and flash it in the status bar.
+    ///
+    /// The hint comes from an ai with the settings of the ai opponent, only the random ai
+    /// leaves it to the default level.
+    fn hint(&mut self) {
+        let tile = self.players.active().tile;
+        let hint = self.hint_ai.hint(&self.board, tile);
+        println!("Hint: column {}, {}", hint.col, hint.reason);
 
+        self.board.select(hint.col - 1);
+        self.update_disp();
+        if self.display_state == DisplayState::Enabled {
+            let led = Led {
+                color: self.players.get(tile).color,
+                blink: Some(Blink {
+                    dur: self.config.win_blink,
+                    int: self.config.win_blink_interval,
+                }),
+            };
+            let frame = AnimationFrame::new(
+                self.config.win_blink_interval * HINT_BLINKS,
+                vec![(hint.col - 1, 0, led)],
+                false,
+            );
             self.display
                 .as_mut()
                 .unwrap()
-                .add_animation(Animation::from_file("./animations/circle.mtxani").unwrap())
+                .add_animation(Animation::new(false, vec![frame], 0, false))
                 .unwrap();
         }
     }
 
+    /// Undo the last turn.
+    ///
+    /// Moves are undone until a local player is on turn again, so that moves of the ai
+    /// are taken back together with the move of the player.
+    fn undo(&mut self) -> GameResult<()> {
+        self.undo_last()?;
+        self.players.swap();
+        while !self.active_is_local() && self.undo_last().is_ok() {
+            self.players.swap();
+        }
+        self.board.set_active(self.players.active().tile);
+        self.update_disp();
+        Ok(())
+    }
+
+    /// Undo the last move on the board, letting the spectators know.
+    fn undo_last(&mut self) -> GameResult<()> {
+        let last = self.board.history().last().copied();
+        self.board.undo_last()?;
+        match last {
+            Some(last) if last.pop => self.spectate(Event::Unpop {
+                tile: last.tile,
+                col: last.x + 1,
+            }),
+            Some(last) => self.spectate(Event::Undo {
+                col: last.x + 1,
+                row: self.board.height() - last.y,
+            }),
+            None => (),
+        }
+        Ok(())
+    }
+
+    /// Redo the last undone turn.
+    ///
+    /// Moves are redone until a local player is on turn again.
+    fn redo(&mut self) -> GameResult<()> {
+        self.board.redo_last()?;
+        self.spectate_move();
+        self.players.swap();
+        while !self.active_is_local() && self.board.redo_last().is_ok() {
+            self.spectate_move();
+            self.players.swap();
+        }
+        self.board.set_active(self.players.active().tile);
+        self.update_disp();
+        Ok(())
+    }
+
     /// x is 0 indexed
     fn drop_ani(&mut self, x: usize) {
-        const FRAMEDUR: Duration = Duration::from_millis(100);
+        let frame_dur = self.config.drop_frame;
 
         let y_end = match self.board.first_empty(x) {
             Ok(y) => y,
@@ -331,13 +1225,13 @@ impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
         // start at 1 to avoid statusbar
         for y in 1..y_end + 1 {
             let frame = AnimationFrame::new(
-                FRAMEDUR,
-                vec![(x, y, LedState::with_color(self.players.active().color))],
+                frame_dur,
+                vec![(x, y, Led::with_color(self.players.active().color))],
                 true,
             );
             frames.push(frame);
         }
-        let sleep_time = FRAMEDUR * frames.len() as u32;
+        let sleep_time = frame_dur * frames.len() as u32;
         let ani = Animation::new(false, frames, 0, false);
         if let Some(disp) = &mut self.display {
             disp.add_animation(ani).unwrap();
@@ -345,18 +1239,28 @@ impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
         }
     }
 
+    /// Board size chosen from the menu, as (columns, playable rows).
+    ///
+    /// The size of a game can't change while it runs, so [`Game::start`] returns once another
+    /// size is chosen. The game can then be started again on a board of that size.
+    pub fn requested_size(&self) -> Option<(usize, usize)> {
+        self.requested_size
+    }
+
     /// Start the game.
+    ///
+    /// Returns when the players quit, or when they choose another board size, see
+    /// [`Game::requested_size`].
     pub fn start(&mut self) {
-        if self.display_state == DisplayState::Enabled {
-            self.display
-                .as_mut()
-                .unwrap()
-                .add_animation(Animation::from_file("./animations/circle.mtxani").unwrap())
-                .unwrap();
-        }
+        self.idle_ani();
+        self.requested_size = None;
 
         loop {
-            println!("Start new round? [Y/n]\t(type \"help\" for help page)");
+            let ai = match self.ai_state {
+                AiState::Enabled => self.ai_level.to_string(),
+                AiState::Disabled => String::from("off"),
+            };
+            println!("Start new round? [Y/n]\t(AI: {ai})\t(type \"help\" for help page)");
             match self.input_handler.get() {
                 Ok(InputValue::Enter) | Ok(InputValue::Yes) | Ok(InputValue::BtnCenter) => {
                     self.start_round()
@@ -375,14 +1279,125 @@ impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
                     };
                     self.players.set_active(ActivePlayer::Player1);
                 }
+                Ok(InputValue::SetAi(level)) => {
+                    self.enable_ai_with(level);
+                    println!("Playing against the {level} AI");
+                    self.players.set_active(ActivePlayer::Player1);
+                }
+                Ok(InputValue::Save(path)) => match self.save(&path) {
+                    Ok(()) => println!("Saved the last round to {path}"),
+                    Err(err) => println!("Could not save the game: {err}"),
+                },
+                Ok(InputValue::Load(path)) => match self.load(&path) {
+                    Ok(()) if matches!(self.board.check_win(), CheckWin::None) => {
+                        println!("Loaded {path}, continuing the game");
+                        self.start_round();
+                    }
+                    Ok(()) => {
+                        print!("{}", self.board);
+                        println!("This game is already over");
+                        self.last_round = self.record();
+                        self.board.reset();
+                        self.update_disp();
+                    }
+                    Err(err) => println!("Could not load the game: {err}"),
+                },
+                Ok(InputValue::Position(Some(position))) => match self.load_position(&position) {
+                    Ok(()) => {
+                        println!("Continuing from the position");
+                        self.start_round();
+                    }
+                    Err(err) => println!("Could not load the position: {err}"),
+                },
+                Ok(InputValue::Position(None)) => {
+                    // x for player 1, o for player 2, rows from the top down, then who is on move
+                    let mut example = self.board.clone();
+                    example.reset();
+                    let width = example.width();
+                    for col in [width / 2 + 1, width / 2 + 1, width / 2] {
+                        drop(example.place(col, example.to_move()));
+                    }
+                    println!("Type position and a position to continue from, like:");
+                    println!("  position {}", example.position());
+                }
+                Ok(InputValue::SetPlayer(number, name)) => {
+                    let tile = match number {
+                        1 => TileType::Player1,
+                        _ => TileType::Player2,
+                    };
+                    match self.set_player(tile, &name) {
+                        Ok(()) => println!("{name} is player {number}"),
+                        Err(err) => println!("Could not change player {number}: {err}"),
+                    }
+                }
+                Ok(InputValue::SetColor(number, color)) => {
+                    let tile = match number {
+                        1 => TileType::Player1,
+                        _ => TileType::Player2,
+                    };
+                    match self.set_color(tile, color) {
+                        Ok(()) => println!("Player {number} plays with {color}"),
+                        Err(err) => println!("Could not change the color: {err}"),
+                    }
+                }
+                Ok(InputValue::Leaderboard(name)) => self.print_leaderboard(name),
+                Ok(InputValue::Host(port)) => {
+                    let port = port.unwrap_or(DEFAULT_PORT);
+                    println!("Waiting for a player to join on port {port}");
+                    if let Err(err) = self.host(("0.0.0.0", port)) {
+                        println!("Could not host the game: {err}");
+                    }
+                }
+                Ok(InputValue::Rules(rules)) => {
+                    self.set_rules(rules);
+                    println!("Playing by the {rules} rules");
+                }
+                Ok(InputValue::Connect(connect)) => match self.set_connect(connect) {
+                    Ok(()) => println!("Connect {connect} to win"),
+                    Err(err) => println!("Could not change the line length: {err}"),
+                },
+                Ok(InputValue::Size(width, height)) => {
+                    if (width, height) == self.size() {
+                        println!("Already playing on a {width}x{height} board");
+                    } else if self.connect() > width.max(height) {
+                        println!(
+                            "Can't connect {} on a {width}x{height} board",
+                            self.connect()
+                        );
+                    } else if BOARD_SIZES.contains(&(width, height)) {
+                        println!("Switching to a {width}x{height} board");
+                        self.requested_size = Some((width, height));
+                        break;
+                    } else {
+                        let sizes = BOARD_SIZES
+                            .iter()
+                            .map(|(w, h)| format!("{w}x{h}"))
+                            .collect::<Vec<String>>();
+                        println!("The board size must be one of {}", sizes.join(", "));
+                    }
+                }
+                Ok(InputValue::Join(addr)) => {
+                    let addr = match addr.contains(':') {
+                        true => addr,
+                        false => format!("{addr}:{DEFAULT_PORT}"),
+                    };
+                    if let Err(err) = self.join(addr.as_str()) {
+                        println!("Could not join the game: {err}");
+                    }
+                }
                 Ok(InputValue::ToggleButtons) => match self.button_state {
                     ButtonState::Enabled => {
                         self.disable_buttons();
                         println!("Toggling buttons off");
                     }
                     ButtonState::Disabled => {
-                        self.enable_buttons().unwrap();
-                        println!("Toggling buttons on");
+                        #[cfg(feature = "gpio")]
+                        {
+                            self.enable_buttons().unwrap();
+                            println!("Toggling buttons on");
+                        }
+                        #[cfg(not(feature = "gpio"))]
+                        println!("Buttons are not supported in this build");
                     }
                 },
                 Ok(InputValue::Help) => {
@@ -390,6 +1405,18 @@ impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
                     println!("Commands");
                     println!("  help\t\t\tshow this page");
                     println!("  toggle ai\t\ttoggle the ai on/off");
+                    println!("  ai <level>\t\tplay against the ai (random, easy, medium, hard, perfect, mcts)");
+                    println!("  player <1|2> <name>\tplay as the given player");
+                    println!("  color <1|2> <color>\tchange the color of a player");
+                    println!("  leaderboard [name]\tshow the standings, or the record of a player");
+                    println!("  save <file>\t\tsave the last round");
+                    println!("  load <file>\t\tload a saved game and continue it");
+                    println!("  position <position>\tcontinue from a position (type position for an example)");
+                    println!("  size <WxH>\t\tplay on another board (6x5, 7x6, 8x7 or 9x7)");
+                    println!("  rules <rules>\t\tplay by the standard or popout rules");
+                    println!("  connect <n>\t\tconnect n pieces to win (3 up to the board size)");
+                    println!("  host [port]\t\twait for a player on another board to join");
+                    println!("  join <address>\tjoin a game hosted on another board");
                     println!("  yes\t\t\tconfirm action (only when applicable)");
                     println!("  no\t\t\tconfirm action (only when applicable)");
                     println!("  KEY: Enter\t\tuse highlighted option (only when applicable)");
@@ -400,6 +1427,7 @@ impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
                     println!("  ai\t\t\tshort for toggle ai");
                     println!("  y\t\t\tshort for yes");
                     println!("  n\t\t\tshort for no");
+                    println!("  lb\t\t\tshort for leaderboard");
                     println!("  exit, stop, q, e, s\tshort for quit");
                 }
                 // Ok(InputValue::BtnUp)
@@ -412,15 +1440,233 @@ impl<'g, const W: usize, const H: usize> Game<'g, W, H> {
     }
 }
 
-impl<'g, const W: usize, const H: usize> Drop for Game<'g, W, H> {
+impl<'g> Drop for Game<'g> {
     fn drop(&mut self) {
         self.board.reset();
         if self.display_state == DisplayState::Enabled {
             self.display.as_mut().unwrap().clear_animations();
         }
         self.update_disp();
-        if let Some(disp) = self.display.take() {
-            drop(disp.stop()); // drop to discard result without warning
+        self.disable_display();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, time::Instant};
+
+    use super::*;
+    use crate::{
+        display::RecordingDisplay,
+        game::button::{Button, ButtonScript},
+    };
+
+    /// Get the column lit in the status bar of the frame, and its color.
+    fn status(frame: &[Vec<Led>]) -> Option<(usize, Color)> {
+        frame[0]
+            .iter()
+            .position(|led| *led != Led::default())
+            .map(|x| (x, frame[0][x].color))
+    }
+
+    #[test]
+    fn shows_round_on_display() {
+        let config = Config {
+            drop_frame: Duration::from_millis(1),
+            ..Config::default()
+        };
+        let [red, yellow] = [config.colors[0], config.colors[1]];
+        let mut game = Game::with_config(config.clone()).unwrap();
+        let display = RecordingDisplay::new();
+        game.set_display(Box::new(display.clone()));
+
+        // player 1 stacks four in the first column, player 2 in the second column
+        let mut presses = vec![Button::Center];
+        for _ in 0..3 {
+            presses.extend([Button::Right, Button::Center, Button::Left, Button::Center]);
         }
+        presses.push(Button::Center); // start the next round
+        let script = ButtonScript::new();
+        let start = Instant::now();
+        for (i, &button) in presses.iter().enumerate() {
+            script.press(button, start + Duration::from_millis(200) * (i as u32 + 1));
+        }
+        game.set_buttons(script.buttons());
+        game.start_round();
+
+        let statuses = display
+            .frames()
+            .iter()
+            .map(|frame| status(frame))
+            .collect::<Vec<_>>();
+        let mut expected = vec![Some((0, red)), Some((0, red)), Some((0, yellow))];
+        for _ in 0..3 {
+            expected.extend([
+                Some((1, yellow)),
+                Some((1, red)),
+                Some((0, red)),
+                Some((0, yellow)),
+            ]);
+        }
+        // the winning move leaves the status bar of the winner, until the board is cleared
+        *expected.last_mut().unwrap() = Some((0, red));
+        expected.push(None);
+        assert_eq!(statuses, expected);
+
+        let frames = display.frames();
+        let won = &frames[frames.len() - 2];
+        for (y, row) in won.iter().enumerate().skip(1) {
+            let first = if y >= 3 { red } else { Color::default() };
+            let second = if y >= 4 { yellow } else { Color::default() };
+            assert_eq!((row[0].color, row[1].color), (first, second), "row {y}");
+        }
+        let last = display.last_frame().unwrap();
+        assert!(last.iter().flatten().all(|led| *led == Led::default()));
+
+        let blink = Led {
+            color: red,
+            blink: Some(Blink {
+                dur: config.win_blink,
+                int: config.win_blink_interval,
+            }),
+        };
+        let leds = (3..7).map(|y| (0, y, blink)).collect();
+        let frame = AnimationFrame::new(config.win_blink_interval, leds, true);
+        let highlight = Animation::new(true, vec![frame], 0, false);
+        assert!(display.animations().contains(&highlight));
+    }
+
+    /// Plays the actions in order, for whichever player is on turn, and writes down the
+    /// position of every turn. Quits when it runs out of actions.
+    struct Scripted {
+        actions: Rc<RefCell<Vec<Action>>>,
+        positions: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl PlayerController for Scripted {
+        fn play(&mut self, turn: &mut Turn<'_, '_>) -> Action {
+            self.positions.borrow_mut().push(turn.board().position());
+            let mut actions = self.actions.borrow_mut();
+            match actions.is_empty() {
+                true => Action::Quit,
+                false => actions.remove(0),
+            }
+        }
+    }
+
+    /// Let both players play the actions in the next round. Returns the positions of the turns.
+    fn play_round(game: &mut Game, actions: Vec<Action>) -> Vec<String> {
+        let actions = Rc::new(RefCell::new(actions));
+        let positions = Rc::new(RefCell::new(Vec::new()));
+        for tile in [TileType::Player1, TileType::Player2] {
+            let scripted = Scripted {
+                actions: actions.clone(),
+                positions: positions.clone(),
+            };
+            game.set_controller(tile, Box::new(scripted)).unwrap();
+        }
+        game.start_round();
+        assert!(actions.borrow().is_empty());
+        positions.take()
+    }
+
+    #[test]
+    fn pops_in_a_round() {
+        let mut game = Game::new().unwrap();
+        game.set_rules(Rules::PopOut);
+        game.load_position("......./......./......./x....../oxxx.../xooo..o x")
+            .unwrap();
+        // player 1 can't pop the tile of player 2, then connects lines for both players
+        play_round(
+            &mut game,
+            vec![Action::Pop(2), Action::Pop(8), Action::Pop(1)],
+        );
+        let round = game.last_round.clone().unwrap();
+        assert_eq!(round.moves, vec![Move::Pop(1)]);
+        assert_eq!(round.result, Outcome::Won(TileType::Player1));
+        assert_eq!(game.players.player1.score, 1);
+    }
+
+    #[test]
+    fn writes_down_the_player_on_move() {
+        let mut game = Game::new().unwrap();
+        game.set_starting_player(TileType::Player2).unwrap();
+        assert_eq!(
+            game.board.position(),
+            "......./......./......./......./......./....... o"
+        );
+
+        // the players take turns starting the rounds
+        let expected = [
+            "......./......./......./......./......./...o... x",
+            "......./......./......./......./......./...x... o",
+            "......./......./......./......./......./...o... x",
+        ];
+        for position in expected {
+            let positions = play_round(&mut game, vec![Action::Place(4)]);
+            assert_eq!(positions[1], position);
+        }
+
+        // the positions can be read back
+        for position in expected {
+            game.load_position(position).unwrap();
+            assert_eq!(game.board.position(), position);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_size() {
+        for (width, height) in [(7, 0), (3, 6)] {
+            let config = Config {
+                width,
+                height,
+                ..Config::default()
+            };
+            assert!(matches!(
+                Game::with_config(config),
+                Err(Error::InvalidConfig(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn plays_on_the_size_of_the_config() {
+        let config = Config {
+            width: 9,
+            height: 7,
+            ..Config::default()
+        };
+        let mut game = Game::with_config(config).unwrap();
+        assert_eq!(game.size(), (9, 7));
+        let position = "........./........./........./........./........./........./....x.... o";
+        game.load_position(position).unwrap();
+        assert_eq!(game.board.get(4, 7).unwrap(), TileType::Player1);
+        assert!(matches!(
+            game.load_position("......./......./......./......./......./...x... o"),
+            Err(Error::PositionSize(7, 6))
+        ));
+    }
+
+    #[test]
+    fn loads_game_started_by_player_2() {
+        let path = std::env::temp_dir().join(format!("connect4-first-{}", std::process::id()));
+        let mut game = Game::new().unwrap();
+        game.set_starting_player(TileType::Player2).unwrap();
+        game.board.place(1, TileType::Player2).unwrap();
+        game.board.place(2, TileType::Player1).unwrap();
+        game.save(&path).unwrap();
+
+        let saved = SavedGame::from_file(&path).unwrap();
+        assert_eq!(
+            (saved.first, saved.sequence()),
+            (TileType::Player2, String::from("12"))
+        );
+
+        let mut loaded = Game::new().unwrap();
+        loaded.load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.board.get(0, 6).unwrap(), TileType::Player2);
+        assert_eq!(loaded.board.get(1, 6).unwrap(), TileType::Player1);
+        assert_eq!(loaded.players.active().tile, TileType::Player2);
     }
 }
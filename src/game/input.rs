@@ -1,56 +1,92 @@
 use std::{
     str::FromStr,
-    sync::mpsc::{channel, Receiver, SendError, Sender, TryRecvError},
+    sync::{
+        mpsc::{channel, Receiver, Sender, TryRecvError},
+        Mutex, OnceLock,
+    },
     thread,
     time::{Duration, Instant},
 };
 
-use crate::game::error::{Error, GameResult};
+use crate::{
+    ai::AiLevel,
+    display::Color,
+    game::{
+        components::Rules,
+        error::{Error, GameResult},
+    },
+};
+
+use super::button::{Buttons, POLL_INTERVAL};
+
+/// Inputs older than this are thrown away, so presses made while the game was busy don't
+/// count as moves.
+pub(super) const INPUT_EXPIRY: Duration = Duration::from_millis(200);
+
+/// A line read from stdin, and when it was read.
+type StdinInput = (GameResult<InputValue>, Instant);
 
-use super::button::ButtonsAsync;
+/// Lines read from stdin.
+///
+/// Stdin is read by a single thread shared by all input handlers, so a game started after
+/// another one doesn't miss the line the old reader was waiting for. The thread stops at the
+/// end of stdin.
+fn stdin() -> &'static Mutex<Receiver<StdinInput>> {
+    static STDIN: OnceLock<Mutex<Receiver<StdinInput>>> = OnceLock::new();
+    STDIN.get_or_init(|| {
+        // communication from thread
+        let (stdin_tx_int, stdin_rx_ext) = channel::<StdinInput>();
+
+        thread::spawn(move || {
+            while let Some(input) = InputValue::get() {
+                if stdin_tx_int.send((input, Instant::now())).is_err() {
+                    break;
+                }
+            }
+        });
+        Mutex::new(stdin_rx_ext)
+    })
+}
 
 #[derive(Debug)]
 pub(super) struct InputHandler {
-    stdin_rx: Receiver<(GameResult<InputValue>, Instant)>,
     btn_rx: Option<Receiver<(InputValue, Instant)>>,
     btn_tx: Option<Sender<Command>>,
-    _remote_rx: Option<Receiver<(InputValue, Instant)>>,
-    _remote_tx: Option<Sender<Command>>,
     buf: Vec<(GameResult<InputValue>, Instant)>,
+    /// If all lines of stdin are used in order, instead of only the latest one.
+    ordered: bool,
 }
 
 impl InputHandler {
     pub(super) fn new() -> Self {
-        // communication from thread
-        let (stdin_tx_int, stdin_rx_ext) = channel::<(GameResult<InputValue>, Instant)>();
-
-        thread::spawn(move || loop {
-            match stdin_tx_int.send((InputValue::get(), Instant::now())) {
-                Ok(()) => (),
-                Err(_) => break,
-            }
-        });
-
         Self {
-            stdin_rx: stdin_rx_ext,
             btn_tx: None,
             btn_rx: None,
-            _remote_tx: None,
-            _remote_rx: None,
             buf: Vec::new(),
+            ordered: false,
         }
     }
 
+    /// Use every line of stdin in order, without throwing old ones away. Used when the input
+    /// is piped in, rather than typed while the game runs.
+    pub(super) fn set_ordered(&mut self, ordered: bool) {
+        self.ordered = ordered;
+    }
+
+    /// Wait for the next input.
+    ///
+    /// Returns `InputValue::Quit` once stdin has ended and no buttons are enabled, as no more
+    /// input can come.
     pub(super) fn get(&mut self) -> GameResult<InputValue> {
+        if self.ordered && self.btn_rx.is_none() {
+            return match stdin().lock().unwrap().recv() {
+                Ok((input, _)) => input,
+                Err(_) => Ok(InputValue::Quit),
+            };
+        }
+
         self.buf.clear();
         loop {
-            if let Some(rx) = &self._remote_rx {
-                while let Ok(input) = rx.try_recv() {
-                    let (input, time) = input;
-                    self.buf.push((Ok(input), time));
-                }
-            }
-
             if let Some(rx) = &self.btn_rx {
                 while let Ok(input) = rx.try_recv() {
                     let (input, time) = input;
@@ -58,38 +94,38 @@ impl InputHandler {
                 }
             }
 
-            while let Ok(input) = self.stdin_rx.try_recv() {
-                self.buf.push(input);
-            }
+            let stdin_rx = stdin().lock().unwrap();
+            let stdin_closed = loop {
+                match stdin_rx.try_recv() {
+                    Ok(input) => self.buf.push(input),
+                    Err(TryRecvError::Empty) => break false,
+                    Err(TryRecvError::Disconnected) => break true,
+                }
+            };
+            drop(stdin_rx);
 
-            self.buf
-                .retain(|input| input.1 + Duration::from_millis(200) > Instant::now());
+            let now = Instant::now();
+            self.buf.retain(|input| !expired(input.1, now));
 
             if let Some(input) = self.buf.pop() {
                 return input.0;
             }
+            if stdin_closed && self.btn_rx.is_none() {
+                return Ok(InputValue::Quit);
+            }
 
             std::thread::sleep(Duration::from_millis(1));
         }
     }
 
-    /// Start receiving inputs from buttons. Requires button pin numbers.
-    pub(super) fn start_buttons(
-        &mut self,
-        btn_up: u8,
-        btn_down: u8,
-        btn_left: u8,
-        btn_right: u8,
-        btn_center: u8,
-    ) -> GameResult<()> {
+    /// Start receiving inputs from the buttons.
+    pub(super) fn start_buttons(&mut self, mut buttons: Buttons) {
         // communication to thread
         let (btn_tx_ext, btn_rx_int) = channel::<Command>();
 
         // communication from thread
         let (btn_tx_int, btn_rx_ext) = channel::<(InputValue, Instant)>();
 
-        let mut buttons = ButtonsAsync::new(btn_up, btn_down, btn_left, btn_right, btn_center)?;
-
         thread::spawn(move || loop {
             match btn_rx_int.try_recv() {
                 Err(TryRecvError::Disconnected) => break,
@@ -99,43 +135,18 @@ impl InputHandler {
                 },
             }
 
-            if buttons.up.read() {
+            let now = Instant::now();
+            for input in buttons.poll(now) {
                 btn_tx_int
-                    .send((InputValue::BtnUp, Instant::now()))
+                    .send((input, now))
                     .expect("Failed to send message");
             }
 
-            if buttons.down.read() {
-                btn_tx_int
-                    .send((InputValue::BtnDown, Instant::now()))
-                    .expect("Failed to send message");
-            }
-
-            if buttons.left.read() {
-                btn_tx_int
-                    .send((InputValue::BtnLeft, Instant::now()))
-                    .expect("Failed to send message");
-            }
-
-            if buttons.right.read() {
-                btn_tx_int
-                    .send((InputValue::BtnRight, Instant::now()))
-                    .expect("Failed to send message");
-            }
-
-            if buttons.center.read() {
-                btn_tx_int
-                    .send((InputValue::BtnCenter, Instant::now()))
-                    .expect("Failed to send message");
-            }
-
-            std::thread::sleep(std::time::Duration::from_millis(20));
+            std::thread::sleep(POLL_INTERVAL);
         });
 
         self.btn_tx = Some(btn_tx_ext);
         self.btn_rx = Some(btn_rx_ext);
-
-        Ok(())
     }
 
     /// Stop receiving input from buttons. Resets gpio pins used by buttons.
@@ -151,42 +162,80 @@ impl InputHandler {
 impl Drop for InputHandler {
     fn drop(&mut self) {
         self.stop_buttons();
-        // self.stop_remote();
     }
 }
 
+/// Check if an input received at `time` is too old to use at `now`.
+pub(super) fn expired(time: Instant, now: Instant) -> bool {
+    time + INPUT_EXPIRY <= now
+}
+
 enum Command {
     Stop,
 }
 
 /// The different types of io input the game can ask for.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub(super) enum InputValue {
     Col(usize),
+    /// Pop an own tile out of the bottom of the column, 1 indexed.
+    Pop(usize),
     Enter,
     Yes,
     No,
     Quit,
     ToggleAi,
+    SetAi(AiLevel),
     Help,
     ToggleButtons,
+    Undo,
+    Redo,
+    Analyse,
+    /// Ask the ai for the best column for the player on turn.
+    Hint,
+    /// Host a network game, on the given port or the default one.
+    Host(Option<u16>),
+    /// Join a network game at the given address.
+    Join(String),
+    /// Save the game to the given file.
+    Save(String),
+    /// Load the game from the given file.
+    Load(String),
+    /// Continue from the given position, or show the position when [None].
+    Position(Option<String>),
+    /// Play by the given rules.
+    Rules(Rules),
+    /// Connect the given number of tiles to win.
+    Connect(usize),
+    /// Play on a board of the given number of columns and playable rows.
+    Size(usize, usize),
+    /// Let the player with the given number (1 or 2) play with the profile of the given name.
+    SetPlayer(usize, String),
+    /// Change the color of the player with the given number (1 or 2).
+    SetColor(usize, Color),
+    /// Show the standings, or the record of the given player against every opponent.
+    Leaderboard(Option<String>),
     BtnUp,
     BtnDown,
+    /// The down button is being held down.
+    BtnHoldDown,
     BtnLeft,
     BtnRight,
     BtnCenter,
-    // AiAdvice, // TODO ask the ai for placement advice (also useful for ai debugging)
 }
 
 impl InputValue {
-    /// Attempt to get input from stdin.
-    fn get() -> GameResult<Self> {
+    /// Attempt to get input from stdin. Returns [None] at the end of stdin.
+    fn get() -> Option<GameResult<Self>> {
         let mut buf = String::new();
 
-        std::io::stdin()
+        match std::io::stdin()
             .read_line(&mut buf)
-            .expect("Failed to read stdin");
-        InputValue::from_str(&buf)
+            .expect("Failed to read stdin")
+        {
+            0 => None,
+            _ => Some(InputValue::from_str(&buf)),
+        }
     }
 }
 
@@ -194,16 +243,85 @@ impl std::str::FromStr for InputValue {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // paths and names keep their case
+        if let Some((cmd, arg)) = s.trim().split_once(' ') {
+            let arg = arg.trim();
+            match cmd.to_lowercase().as_str() {
+                "save" => return Ok(Self::Save(arg.to_string())),
+                "load" => return Ok(Self::Load(arg.to_string())),
+                "position" | "pos" => return Ok(Self::Position(Some(arg.to_string()))),
+                "leaderboard" | "lb" => return Ok(Self::Leaderboard(Some(arg.to_string()))),
+                cmd @ ("player" | "color" | "colour") => {
+                    let invalid = || Error::InvalidInput(s.trim().to_string());
+                    let (number, value) = arg.split_once(' ').ok_or_else(invalid)?;
+                    let number = match number {
+                        "1" => 1,
+                        "2" => 2,
+                        _ => return Err(invalid()),
+                    };
+                    return match cmd {
+                        "player" => Ok(Self::SetPlayer(number, value.trim().to_string())),
+                        _ => Ok(Self::SetColor(number, value.trim().parse()?)),
+                    };
+                }
+                _ => (),
+            }
+        }
+
         match s.to_lowercase().trim() {
             "" => Ok(Self::Enter),
             "yes" | "y" => Ok(Self::Yes),
             "no" | "n" => Ok(Self::No),
             "stop" | "exit" | "quit" | "s" | "e" | "q" => Ok(Self::Quit),
             "ai" | "toggle ai" => Ok(Self::ToggleAi),
+            cmd if cmd.starts_with("ai ") => Ok(Self::SetAi(cmd[3..].parse()?)),
             "help" | "h" | "?" => Ok(Self::Help),
             "button" | "buttons" => Ok(Self::ToggleButtons),
+            "undo" | "u" => Ok(Self::Undo),
+            "redo" | "r" => Ok(Self::Redo),
+            "analyse" | "analyze" => Ok(Self::Analyse),
+            "hint" => Ok(Self::Hint),
+            "position" | "pos" => Ok(Self::Position(None)),
+            "leaderboard" | "lb" => Ok(Self::Leaderboard(None)),
+            "host" => Ok(Self::Host(None)),
+            cmd if cmd.starts_with("host ") => match cmd[5..].trim().parse() {
+                Ok(port) => Ok(Self::Host(Some(port))),
+                Err(_) => Err(Error::InvalidInput(cmd.to_string())),
+            },
+            cmd if cmd.starts_with("rules ") => Ok(Self::Rules(cmd[6..].parse()?)),
+            cmd if cmd.starts_with("connect ") => match cmd[8..].trim().parse() {
+                Ok(connect) => Ok(Self::Connect(connect)),
+                Err(_) => Err(Error::InvalidInput(cmd.to_string())),
+            },
+            cmd if cmd.starts_with("pop ") || cmd.starts_with('p') => {
+                let col = cmd.strip_prefix("pop ").unwrap_or(&cmd[1..]).trim();
+                match col.parse() {
+                    Ok(col) => Ok(Self::Pop(col)),
+                    Err(_) => Err(Error::InvalidInput(cmd.to_string())),
+                }
+            }
+            cmd if cmd.starts_with("join ") => Ok(Self::Join(cmd[5..].trim().to_string())),
+            cmd if cmd.starts_with("size ") => cmd[5..]
+                .trim()
+                .split_once('x')
+                .and_then(|(w, h)| Some(Self::Size(w.parse().ok()?, h.parse().ok()?)))
+                .ok_or_else(|| Error::InvalidInput(cmd.to_string())),
             col if col.parse::<usize>().is_ok() => Ok(Self::Col(col.parse::<usize>().unwrap())),
             str => Err(Error::InvalidInput(str.to_string())),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inputs_expire() {
+        let time = Instant::now();
+        assert!(!expired(time, time));
+        assert!(!expired(time, time + Duration::from_millis(199)));
+        assert!(expired(time, time + Duration::from_millis(200)));
+        assert!(expired(time, time + Duration::from_secs(1)));
+    }
+}
@@ -1,8 +1,27 @@
+#[doc(hidden)]
+pub mod bench;
+mod bitboard;
 pub(crate) mod board;
 pub mod button;
 pub(crate) mod components;
+pub(crate) mod config;
+mod controller;
 pub(crate) mod error;
+#[allow(clippy::module_inception)]
 mod game;
 mod input;
+mod network;
+mod notation;
+mod profile;
+pub(crate) mod search_board;
+mod spectator;
 
+pub use button::{Button, ButtonScript, ButtonSource, Buttons, SimulatedButton};
+pub use components::{GameRecord, Move, Rules, TileType, DEFAULT_CONNECT};
+pub use config::{ButtonPins, Config, DisplayPins, BOARD_SIZES};
+pub use controller::{Action, AiPlayer, BoardView, LocalPlayer, PlayerController, Turn};
+pub use error::Error;
 pub use game::Game;
+pub use network::{NetworkPlayer, DEFAULT_PORT, PROTOCOL_VERSION};
+pub use notation::{Outcome, SavedGame};
+pub use profile::{Profile, Profiles, Record};
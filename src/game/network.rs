@@ -0,0 +1,432 @@
+use std::{
+    io::{BufRead, BufReader, ErrorKind, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    str::FromStr,
+};
+
+use crate::game::{
+    components::{Rules, TileType},
+    controller::{Action, PlayerController, Turn},
+    error::{Error, GameResult},
+};
+
+/// Version of the line protocol spoken between two games.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Port used when hosting without giving one.
+pub const DEFAULT_PORT: u16 = 4444;
+
+/// A line of the protocol.
+///
+/// Both sides start by sending `hello <version> <width> <height> <connect> <rules> <name>`,
+/// `height` being the number of playable rows and `connect` the number of tiles to connect to
+/// win. After that moves are sent as
+/// `move <col>`, and pops as `pop <col>`, the column being 1 indexed. A move that can't be
+/// played is answered with `error <reason>`, and `quit` stops the round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Message {
+    Hello {
+        version: u32,
+        width: usize,
+        height: usize,
+        connect: usize,
+        rules: Rules,
+        name: String,
+    },
+    Move(usize),
+    Pop(usize),
+    Error(String),
+    Quit,
+}
+
+impl FromStr for Message {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::Protocol(format!("invalid message: {s}"));
+        let (cmd, args) = s.trim().split_once(' ').unwrap_or((s.trim(), ""));
+        match cmd {
+            "hello" => {
+                let mut args = args.splitn(6, ' ');
+                let mut number = || {
+                    args.next()
+                        .and_then(|arg| arg.parse().ok())
+                        .ok_or_else(invalid)
+                };
+                let version = number()? as u32;
+                let width = number()?;
+                let height = number()?;
+                let connect = number()?;
+                let rules = args
+                    .next()
+                    .and_then(|arg| arg.parse().ok())
+                    .ok_or_else(invalid)?;
+                Ok(Self::Hello {
+                    version,
+                    width,
+                    height,
+                    connect,
+                    rules,
+                    name: args.next().unwrap_or_default().to_string(),
+                })
+            }
+            "move" => Ok(Self::Move(args.parse().map_err(|_| invalid())?)),
+            "pop" => Ok(Self::Pop(args.parse().map_err(|_| invalid())?)),
+            "error" => Ok(Self::Error(args.to_string())),
+            "quit" => Ok(Self::Quit),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Message::Hello {
+                version,
+                width,
+                height,
+                connect,
+                rules,
+                name,
+            } => write!(
+                f,
+                "hello {version} {width} {height} {connect} {rules} {name}"
+            ),
+            Message::Move(col) => write!(f, "move {col}"),
+            Message::Pop(col) => write!(f, "pop {col}"),
+            Message::Error(reason) => write!(f, "error {reason}"),
+            Message::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+/// A player at another table, connected over TCP.
+///
+/// The moves of the local player are sent to the peer as they are made, and the moves of the
+/// peer are checked before they are played.
+pub struct NetworkPlayer {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    /// Name the peer introduced itself with.
+    name: String,
+    /// If the connection has been lost.
+    disconnected: bool,
+}
+
+impl NetworkPlayer {
+    /// Wait for a peer to connect to the given address, to play a game on a board of the given
+    /// size, as (columns, playable rows), where `connect` tiles have to be connected to win.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the connection fails.
+    ///
+    /// Returns `Error::Protocol` if the peer speaks another version, or plays on another board
+    /// or by other rules.
+    pub fn host(
+        addr: impl ToSocketAddrs,
+        name: &str,
+        size: (usize, usize),
+        connect: usize,
+        rules: Rules,
+    ) -> GameResult<Self> {
+        Self::accept(TcpListener::bind(addr)?, name, size, connect, rules)
+    }
+
+    /// Wait for a peer to connect to the listener.
+    fn accept(
+        listener: TcpListener,
+        name: &str,
+        size: (usize, usize),
+        connect: usize,
+        rules: Rules,
+    ) -> GameResult<Self> {
+        let (stream, peer) = listener.accept()?;
+        log::info!("Network: {peer} connected");
+        Self::handshake(stream, name, size, connect, rules)
+    }
+
+    /// Connect to a hosting game at the given address, playing a game on a board of the given
+    /// size, as (columns, playable rows), where `connect` tiles have to be connected to win.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the connection fails.
+    ///
+    /// Returns `Error::Protocol` if the host speaks another version, or plays on another board
+    /// or by other rules.
+    pub fn connect(
+        addr: impl ToSocketAddrs,
+        name: &str,
+        size: (usize, usize),
+        connect: usize,
+        rules: Rules,
+    ) -> GameResult<Self> {
+        Self::handshake(TcpStream::connect(addr)?, name, size, connect, rules)
+    }
+
+    /// Introduce both sides to each other.
+    fn handshake(
+        stream: TcpStream,
+        name: &str,
+        size: (usize, usize),
+        connect: usize,
+        rules: Rules,
+    ) -> GameResult<Self> {
+        let mut player = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            name: String::new(),
+            disconnected: false,
+        };
+        player.send(&Message::Hello {
+            version: PROTOCOL_VERSION,
+            width: size.0,
+            height: size.1,
+            connect,
+            rules,
+            name: name.to_string(),
+        })?;
+
+        match player.receive()? {
+            Message::Hello { version, .. } if version != PROTOCOL_VERSION => {
+                let reason = format!("unsupported protocol version {version}");
+                drop(player.send(&Message::Error(reason.clone()))); // peer will hang up anyway
+                Err(Error::Protocol(reason))
+            }
+            Message::Hello { width, height, .. } if (width, height) != size => {
+                let reason = format!("board is {}x{}, not {width}x{height}", size.0, size.1);
+                drop(player.send(&Message::Error(reason.clone())));
+                Err(Error::Protocol(reason))
+            }
+            Message::Hello { connect: peer, .. } if peer != connect => {
+                let reason = format!("game is played connecting {connect}, not {peer}");
+                drop(player.send(&Message::Error(reason.clone())));
+                Err(Error::Protocol(reason))
+            }
+            Message::Hello { rules: peer, .. } if peer != rules => {
+                let reason = format!("game is played by the {rules} rules, not {peer}");
+                drop(player.send(&Message::Error(reason.clone())));
+                Err(Error::Protocol(reason))
+            }
+            Message::Hello { name, .. } => {
+                player.name = name;
+                Ok(player)
+            }
+            Message::Error(reason) => Err(Error::Protocol(reason)),
+            msg => Err(Error::Protocol(format!("expected hello, got: {msg}"))),
+        }
+    }
+
+    fn send(&mut self, msg: &Message) -> GameResult<()> {
+        writeln!(self.writer, "{msg}")?;
+        Ok(())
+    }
+
+    fn receive(&mut self) -> GameResult<Message> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
+        line.parse()
+    }
+
+    /// Check if the peer can place in the column.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidColumn` if the column is outside the board.
+    ///
+    /// Returns `Error::ColumnFull` if the column has no empty spots left.
+    fn check_move(turn: &Turn<'_, '_>, col: usize) -> GameResult<()> {
+        if col < 1 || col > turn.board().width() {
+            Err(Error::InvalidColumn)
+        } else if !turn.board().can_place(col) {
+            Err(Error::ColumnFull)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check if the peer can pop a tile out of the column.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Protocol` if the game is not played by the PopOut rules.
+    ///
+    /// Returns `Error::InvalidColumn` if the column is outside the board.
+    ///
+    /// Returns `Error::CantPop` if the bottom tile of the column is not of the peer.
+    fn check_pop(turn: &Turn<'_, '_>, col: usize) -> GameResult<()> {
+        if turn.rules() != Rules::PopOut {
+            Err(Error::Protocol(format!(
+                "pops are not allowed by the {} rules",
+                turn.rules()
+            )))
+        } else if col < 1 || col > turn.board().width() {
+            Err(Error::InvalidColumn)
+        } else if !turn.board().can_pop(col, turn.tile()) {
+            Err(Error::CantPop)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl PlayerController for NetworkPlayer {
+    fn play(&mut self, turn: &mut Turn<'_, '_>) -> Action {
+        while !self.disconnected {
+            let err = match self.receive() {
+                Ok(Message::Move(col)) => match Self::check_move(turn, col) {
+                    Ok(()) => return Action::Place(col),
+                    Err(err) => {
+                        log::warn!("Network: Rejected move in column {col}: {err}");
+                        err
+                    }
+                },
+                Ok(Message::Pop(col)) => match Self::check_pop(turn, col) {
+                    Ok(()) => return Action::Pop(col),
+                    Err(err) => {
+                        log::warn!("Network: Rejected pop in column {col}: {err}");
+                        err
+                    }
+                },
+                Ok(Message::Quit) => return Action::Quit,
+                Ok(Message::Error(reason)) => {
+                    println!("{} reported an error: {reason}", self.name);
+                    return Action::Quit;
+                }
+                Ok(msg) => Error::Protocol(format!("unexpected message: {msg}")),
+                Err(Error::Io(err)) => {
+                    log::error!("Network: {err}");
+                    self.disconnected = true;
+                    break;
+                }
+                Err(err) => err,
+            };
+            if self.send(&Message::Error(err.to_string())).is_err() {
+                self.disconnected = true;
+            }
+        }
+        println!("Lost connection to {}", self.name);
+        Action::Quit
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+
+    fn opponent_moved(&mut self, col: usize, _tile: TileType) {
+        if let Err(err) = self.send(&Message::Move(col)) {
+            log::error!("Network: {err}");
+            self.disconnected = true;
+        }
+    }
+
+    fn opponent_popped(&mut self, col: usize, _tile: TileType) {
+        if let Err(err) = self.send(&Message::Pop(col)) {
+            log::error!("Network: {err}");
+            self.disconnected = true;
+        }
+    }
+
+    fn opponent_quit(&mut self) {
+        drop(self.send(&Message::Quit)); // drop to discard result without warning
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::game::game::Game;
+
+    /// Host a game on a free port of the loopback address, and connect to it.
+    fn connected() -> (NetworkPlayer, NetworkPlayer) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let host =
+            thread::spawn(move || NetworkPlayer::accept(listener, "a", (7, 6), 4, Rules::Standard));
+        let peer = NetworkPlayer::connect(addr, "b", (7, 6), 4, Rules::Standard).unwrap();
+        (host.join().unwrap().unwrap(), peer)
+    }
+
+    #[test]
+    fn exchanges_moves() {
+        let (mut host, mut peer) = connected();
+        assert_eq!(host.name(), Some(String::from("b")));
+        assert_eq!(peer.name(), Some(String::from("a")));
+
+        let mut game = Game::new().unwrap();
+        host.opponent_moved(4, TileType::Player1);
+        assert_eq!(peer.play(&mut Turn::new(&mut game)), Action::Place(4));
+        peer.opponent_moved(3, TileType::Player2);
+        assert_eq!(host.play(&mut Turn::new(&mut game)), Action::Place(3));
+        host.opponent_quit();
+        assert_eq!(peer.play(&mut Turn::new(&mut game)), Action::Quit);
+    }
+
+    #[test]
+    fn rejects_illegal_moves() {
+        let (mut host, mut peer) = connected();
+        let mut game = Game::new().unwrap();
+        peer.send(&Message::Move(8)).unwrap();
+        peer.send(&Message::Pop(1)).unwrap();
+        peer.send(&Message::Move(2)).unwrap();
+        assert_eq!(host.play(&mut Turn::new(&mut game)), Action::Place(2));
+
+        let invalid_column = Error::InvalidColumn.to_string();
+        assert_eq!(peer.receive().unwrap(), Message::Error(invalid_column));
+        let pop_not_allowed =
+            Error::Protocol(String::from("pops are not allowed by the standard rules"));
+        assert_eq!(
+            peer.receive().unwrap(),
+            Message::Error(pop_not_allowed.to_string())
+        );
+    }
+
+    #[test]
+    fn checks_the_version() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let host = thread::spawn(move || {
+            NetworkPlayer::accept(listener, "a", (7, 6), 4, Rules::Standard).err()
+        });
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let newer = PROTOCOL_VERSION + 1;
+        writeln!(stream, "hello {newer} 7 6 4 standard b").unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let hello = Message::Hello {
+            version: PROTOCOL_VERSION,
+            width: 7,
+            height: 6,
+            connect: 4,
+            rules: Rules::Standard,
+            name: String::from("a"),
+        };
+        assert_eq!(lines.next().unwrap().unwrap(), hello.to_string());
+        let error = format!("error unsupported protocol version {newer}");
+        assert_eq!(lines.next().unwrap().unwrap(), error);
+        assert!(matches!(host.join().unwrap(), Some(Error::Protocol(_))));
+    }
+
+    #[test]
+    fn checks_the_board() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let host = thread::spawn(move || {
+            NetworkPlayer::accept(listener, "a", (7, 6), 4, Rules::Standard).err()
+        });
+        let peer = NetworkPlayer::connect(addr, "b", (8, 7), 4, Rules::Standard);
+        // the sizes are compared without the status bar
+        assert!(matches!(peer, Err(Error::Protocol(reason)) if reason == "board is 8x7, not 7x6"));
+        assert!(matches!(host.join().unwrap(), Some(Error::Protocol(_))));
+    }
+}
@@ -0,0 +1,270 @@
+use std::{path::Path, str::FromStr};
+
+use crate::{
+    display::Color,
+    game::{
+        components::{Move, Rules, TileType, DEFAULT_CONNECT},
+        error::{Error, GameResult},
+    },
+};
+
+/// Version of the save file format.
+const FORMAT_VERSION: u32 = 1;
+
+/// How a saved game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The game is not finished yet.
+    Ongoing,
+    /// The player with the given tile won.
+    Won(TileType),
+    /// The board filled up without a winner.
+    Draw,
+}
+
+/// A game written down in a portable text format.
+///
+/// The moves are stored as a sequence of 1 indexed columns, like `4453`, the notation used by
+/// most Connect 4 tools. A save file looks like this:
+///
+/// ```text
+/// connect4 1
+/// size 7x6
+/// player1 red a
+/// player2 yellow b
+/// result ongoing
+/// moves 4453
+/// ```
+///
+/// Games where another number of tiles than 4 has to be connected have a line like
+/// `connect 5`. Games played by the PopOut rules have a `rules popout` line, and their pops are
+/// written as `p` followed by the column, like `4453p4`. Games that didn't start on an empty
+/// board have a line like `position ......./......./......./......./...o.../..xxo.. x` with the
+/// position the moves were played from, the player on move making the first move. Games on
+/// an empty board where player 2 made the first move have a `first 2` line.
+///
+/// A file holding only the moves, as exported by other tools, can be read as well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedGame {
+    /// Number of columns.
+    pub width: usize,
+    /// Number of playable rows.
+    pub height: usize,
+    /// Number of tiles a player has to connect to win.
+    pub connect: usize,
+    /// Rules the game is played by.
+    pub rules: Rules,
+    /// Position the moves were played from, [None] for the empty board.
+    pub position: Option<String>,
+    /// Player who made the first move.
+    pub first: TileType,
+    /// Name and color of player 1 and player 2.
+    pub players: [(String, Color); 2],
+    /// The moves, in order.
+    pub moves: Vec<Move>,
+    /// How the game ended.
+    pub result: Outcome,
+}
+
+impl SavedGame {
+    /// Read a saved game from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file can't be read.
+    ///
+    /// Returns `Error::Notation` if the file is not a valid game.
+    pub fn from_file(path: impl AsRef<Path>) -> GameResult<Self> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Write the game to a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file can't be written.
+    pub fn to_file(&self, path: impl AsRef<Path>) -> GameResult<()> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// The moves as a column sequence, like `4453`, pops written like `p4`.
+    pub fn sequence(&self) -> String {
+        self.moves.iter().map(|mv| mv.to_string()).collect()
+    }
+}
+
+/// Parse a column sequence like `4453`, with pops written like `p4`.
+fn parse_moves(s: &str) -> GameResult<Vec<Move>> {
+    let mut moves = Vec::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        let invalid =
+            |c| Error::Notation(format!("move {}: invalid column '{c}'", moves.len() + 1));
+        let (pop, c) = match c {
+            'p' => (true, chars.next().ok_or_else(|| invalid(c))?),
+            c => (false, c),
+        };
+        match c.to_digit(10) {
+            Some(col) if col > 0 && pop => moves.push(Move::Pop(col as usize)),
+            Some(col) if col > 0 => moves.push(Move::Place(col as usize)),
+            _ => return Err(invalid(c)),
+        }
+    }
+    Ok(moves)
+}
+
+impl FromStr for SavedGame {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut game = SavedGame {
+            width: 7,
+            height: 6,
+            connect: DEFAULT_CONNECT,
+            rules: Rules::Standard,
+            position: None,
+            first: TileType::Player1,
+            players: [
+                (String::from("a"), Color::Red),
+                (String::from("b"), Color::Yellow),
+            ],
+            moves: Vec::new(),
+            result: Outcome::Ongoing,
+        };
+
+        let lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .collect::<Vec<(usize, &str)>>();
+
+        // just the moves
+        if let [(_, moves)] = lines.as_slice() {
+            if moves.chars().all(|c| c.is_ascii_digit() || c == 'p') {
+                game.moves = parse_moves(moves)?;
+                // only PopOut games have pops
+                if game.moves.iter().any(|mv| matches!(mv, Move::Pop(_))) {
+                    game.rules = Rules::PopOut;
+                }
+                return Ok(game);
+            }
+        }
+
+        let mut first = None;
+        for (line, text) in lines {
+            let invalid = |msg: &str| Error::Notation(format!("line {line}: {msg}"));
+            let (key, value) = text.split_once(' ').unwrap_or((text, ""));
+            let value = value.trim();
+            match key {
+                "connect4" => match value.parse::<u32>() {
+                    Ok(FORMAT_VERSION) => (),
+                    Ok(version) => return Err(invalid(&format!("unsupported version {version}"))),
+                    Err(_) => return Err(invalid("expected a version number")),
+                },
+                "size" => {
+                    let size = value.split_once('x').and_then(|(w, h)| {
+                        Some((w.parse::<usize>().ok()?, h.parse::<usize>().ok()?))
+                    });
+                    (game.width, game.height) =
+                        size.ok_or_else(|| invalid("expected a size like 7x6"))?;
+                }
+                "connect" => {
+                    game.connect = value
+                        .parse()
+                        .map_err(|_| invalid("expected the number of tiles to connect"))?;
+                }
+                "rules" => {
+                    game.rules = value
+                        .parse()
+                        .map_err(|_| invalid(&format!("unknown rules '{value}'")))?;
+                }
+                "position" => game.position = Some(value.to_string()),
+                "first" => {
+                    first = match value {
+                        "1" => Some((line, TileType::Player1)),
+                        "2" => Some((line, TileType::Player2)),
+                        _ => return Err(invalid("expected player 1 or 2")),
+                    }
+                }
+                "player1" | "player2" => {
+                    let (color, name) = value.split_once(' ').unwrap_or((value, ""));
+                    let color = color
+                        .parse()
+                        .map_err(|_| invalid(&format!("unknown color '{color}'")))?;
+                    if name.is_empty() {
+                        return Err(invalid("missing player name"));
+                    }
+                    let i = if key == "player1" { 0 } else { 1 };
+                    game.players[i] = (name.to_string(), color);
+                }
+                "result" => {
+                    game.result = match value {
+                        "ongoing" | "*" => Outcome::Ongoing,
+                        "1-0" => Outcome::Won(TileType::Player1),
+                        "0-1" => Outcome::Won(TileType::Player2),
+                        "draw" | "1/2-1/2" => Outcome::Draw,
+                        _ => return Err(invalid(&format!("unknown result '{value}'"))),
+                    }
+                }
+                "moves" => {
+                    game.moves = parse_moves(value).map_err(|err| match err {
+                        Error::Notation(reason) => invalid(&reason),
+                        err => err,
+                    })?
+                }
+                _ => return Err(invalid(&format!("unknown key '{key}'"))),
+            }
+        }
+
+        // the player on move in the position makes the first move
+        let on_move =
+            game.position
+                .as_deref()
+                .map(|position| match position.trim_end().rsplit(' ').next() {
+                    Some("o") => TileType::Player2,
+                    _ => TileType::Player1,
+                });
+        game.first = match (first, on_move) {
+            (Some((line, first)), Some(on_move)) if first != on_move => {
+                return Err(Error::Notation(format!(
+                    "line {line}: the first move is not made by the player on move in the position"
+                )))
+            }
+            (_, Some(first)) | (Some((_, first)), None) => first,
+            (None, None) => TileType::Player1,
+        };
+        Ok(game)
+    }
+}
+
+impl std::fmt::Display for SavedGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let result = match self.result {
+            Outcome::Ongoing => "ongoing",
+            Outcome::Won(TileType::Player1) => "1-0",
+            Outcome::Won(_) => "0-1",
+            Outcome::Draw => "draw",
+        };
+        writeln!(f, "connect4 {FORMAT_VERSION}")?;
+        writeln!(f, "size {}x{}", self.width, self.height)?;
+        // standard games stay readable by older versions
+        if self.connect != DEFAULT_CONNECT {
+            writeln!(f, "connect {}", self.connect)?;
+        }
+        if self.rules != Rules::Standard {
+            writeln!(f, "rules {}", self.rules)?;
+        }
+        if self.first == TileType::Player2 && self.position.is_none() {
+            writeln!(f, "first 2")?;
+        }
+        if let Some(position) = &self.position {
+            writeln!(f, "position {position}")?;
+        }
+        writeln!(f, "player1 {} {}", self.players[0].1, self.players[0].0)?;
+        writeln!(f, "player2 {} {}", self.players[1].1, self.players[1].0)?;
+        writeln!(f, "result {result}")?;
+        writeln!(f, "moves {}", self.sequence())
+    }
+}
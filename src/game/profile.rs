@@ -0,0 +1,443 @@
+use std::{
+    collections::BTreeMap,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    display::Color,
+    game::error::{Error, GameResult},
+};
+
+/// Version of the profile file format.
+const FORMAT_VERSION: u32 = 1;
+
+/// Wins, losses and draws of a player.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Record {
+    /// Number of games won.
+    pub wins: usize,
+    /// Number of games lost.
+    pub losses: usize,
+    /// Number of games that ended in a draw.
+    pub draws: usize,
+}
+
+impl Record {
+    /// A single won game.
+    pub const WIN: Self = Self {
+        wins: 1,
+        losses: 0,
+        draws: 0,
+    };
+    /// A single lost game.
+    pub const LOSS: Self = Self {
+        wins: 0,
+        losses: 1,
+        draws: 0,
+    };
+    /// A single draw.
+    pub const DRAW: Self = Self {
+        wins: 0,
+        losses: 0,
+        draws: 1,
+    };
+
+    /// Number of games played.
+    pub fn played(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+
+    /// The same games, seen from the side of the opponent.
+    pub fn inverse(self) -> Self {
+        Self {
+            wins: self.losses,
+            losses: self.wins,
+            draws: self.draws,
+        }
+    }
+
+    /// Add the games of another record.
+    pub fn add(&mut self, other: Self) {
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.draws += other.draws;
+    }
+}
+
+impl std::fmt::Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}W {}L {}D", self.wins, self.losses, self.draws)
+    }
+}
+
+/// A player known to the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    /// Name of the player.
+    pub name: String,
+    /// Color the player likes to play with.
+    pub color: Color,
+    /// Record against every opponent, by name. Ai opponents are named after their level, like
+    /// `AI (hard)`.
+    pub opponents: BTreeMap<String, Record>,
+}
+
+impl Profile {
+    /// Create a new profile without any games played.
+    pub fn new(name: &str, color: Color) -> Self {
+        Self {
+            name: name.to_string(),
+            color,
+            opponents: BTreeMap::new(),
+        }
+    }
+
+    /// Record against all opponents together.
+    pub fn total(&self) -> Record {
+        let mut total = Record::default();
+        for record in self.opponents.values() {
+            total.add(*record);
+        }
+        total
+    }
+}
+
+/// Profiles of all players, stored in a file so the standings survive a restart.
+///
+/// The file is plain text and looks like this:
+///
+/// ```text
+/// connect4-profiles 1
+///
+/// [alice]
+/// color blue
+/// vs AI (hard) = 3 1 0
+/// vs bob = 2 2 1
+/// ```
+///
+/// Every `vs` line holds the wins, losses and draws against that opponent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profiles {
+    /// File the profiles are saved to, [None] if they are only kept in memory.
+    path: Option<PathBuf>,
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    /// Create an empty set of profiles, kept in memory only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the profiles stored in the file. The file is created on the first save if it
+    /// doesn't exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file can't be read.
+    ///
+    /// Returns `Error::InvalidProfiles` if the file is not a valid profile file.
+    pub fn open(path: impl AsRef<Path>) -> GameResult<Self> {
+        let path = path.as_ref();
+        let mut profiles = match std::fs::read_to_string(path) {
+            Ok(s) => s.parse()?,
+            Err(err) if err.kind() == ErrorKind::NotFound => Self::new(),
+            Err(err) => return Err(err.into()),
+        };
+        profiles.path = Some(path.to_path_buf());
+        Ok(profiles)
+    }
+
+    /// Write the profiles back to their file. Does nothing for profiles kept in memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the file can't be written.
+    pub fn save(&self) -> GameResult<()> {
+        if let Some(path) = &self.path {
+            // write to a temporary file first, so a power cut can't leave half a file behind
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, self.to_string())?;
+            std::fs::rename(tmp, path)?;
+        }
+        Ok(())
+    }
+
+    /// Get the profile of the player with the given name.
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    /// Get the profile of the player with the given name, creating it with the given color if
+    /// it doesn't exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidInput` if the name is empty.
+    pub fn get_or_create(&mut self, name: &str, color: Color) -> GameResult<&mut Profile> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(Error::InvalidInput(String::from("empty player name")));
+        }
+        let i = match self
+            .profiles
+            .iter()
+            .position(|profile| profile.name == name)
+        {
+            Some(i) => i,
+            None => {
+                self.profiles.push(Profile::new(name, color));
+                self.profiles.len() - 1
+            }
+        };
+        Ok(&mut self.profiles[i])
+    }
+
+    /// Add the result of a game to the profile of the player, if the player has a profile.
+    ///
+    /// Returns if the result was added.
+    pub fn add_result(&mut self, name: &str, opponent: &str, result: Record) -> bool {
+        match self
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+        {
+            Some(profile) => {
+                profile
+                    .opponents
+                    .entry(opponent.to_string())
+                    .or_default()
+                    .add(result);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// All profiles.
+    pub fn iter(&self) -> impl Iterator<Item = &Profile> {
+        self.profiles.iter()
+    }
+
+    /// Standings of all players, best first.
+    ///
+    /// Opponents without a profile, like the ai levels or players on another board, are
+    /// ranked as well, by the games played against them.
+    pub fn leaderboard(&self) -> Vec<(String, Record)> {
+        let mut standings = BTreeMap::<String, Record>::new();
+        for profile in &self.profiles {
+            standings.entry(profile.name.clone()).or_default();
+            for (opponent, record) in &profile.opponents {
+                match self.get(opponent) {
+                    // counted in the profile of the opponent itself
+                    Some(_) => (),
+                    None => standings
+                        .entry(opponent.clone())
+                        .or_default()
+                        .add(record.inverse()),
+                }
+            }
+            standings
+                .get_mut(&profile.name)
+                .expect("inserted above")
+                .add(profile.total());
+        }
+
+        let mut standings = standings.into_iter().collect::<Vec<(String, Record)>>();
+        standings.sort_by(|(name_a, a), (name_b, b)| {
+            let points = |r: &Record| 2 * r.wins + r.draws;
+            points(b)
+                .cmp(&points(a))
+                .then(a.played().cmp(&b.played()))
+                .then(name_a.cmp(name_b))
+        });
+        standings
+    }
+}
+
+impl FromStr for Profiles {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut profiles = Self::new();
+        let mut version = None;
+
+        for (line, text) in s.lines().enumerate().map(|(i, line)| (i + 1, line.trim())) {
+            let invalid = |msg: &str| Error::InvalidProfiles(format!("line {line}: {msg}"));
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+
+            if version.is_none() {
+                version = match text.split_once(' ') {
+                    Some(("connect4-profiles", v)) => match v.trim().parse::<u32>() {
+                        Ok(FORMAT_VERSION) => Some(FORMAT_VERSION),
+                        Ok(v) => return Err(invalid(&format!("unsupported version {v}"))),
+                        Err(_) => return Err(invalid("expected a version number")),
+                    },
+                    _ => return Err(invalid("expected 'connect4-profiles <version>'")),
+                };
+                continue;
+            }
+
+            if let Some(name) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+                let name = name.trim();
+                if name.is_empty() {
+                    return Err(invalid("empty player name"));
+                }
+                if profiles.get(name).is_some() {
+                    return Err(invalid(&format!("duplicate player '{name}'")));
+                }
+                profiles.profiles.push(Profile::new(name, Color::Off));
+                continue;
+            }
+
+            let profile = profiles
+                .profiles
+                .last_mut()
+                .ok_or_else(|| invalid("expected a player like [name]"))?;
+            let (key, value) = text.split_once(' ').unwrap_or((text, ""));
+            match key {
+                "color" => {
+                    profile.color = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| invalid(&format!("unknown color '{}'", value.trim())))?;
+                }
+                "vs" => {
+                    let (opponent, games) = value
+                        .rsplit_once('=')
+                        .ok_or_else(|| invalid("expected 'vs <name> = <wins> <losses> <draws>'"))?;
+                    let games = games
+                        .split_whitespace()
+                        .map(|n| n.parse::<usize>())
+                        .collect::<Result<Vec<usize>, _>>();
+                    let record = match games.as_deref() {
+                        Ok(&[wins, losses, draws]) => Record {
+                            wins,
+                            losses,
+                            draws,
+                        },
+                        _ => return Err(invalid("expected <wins> <losses> <draws>")),
+                    };
+                    let opponent = opponent.trim();
+                    if opponent.is_empty() {
+                        return Err(invalid("empty opponent name"));
+                    }
+                    profile
+                        .opponents
+                        .entry(opponent.to_string())
+                        .or_default()
+                        .add(record);
+                }
+                _ => return Err(invalid(&format!("unknown key '{key}'"))),
+            }
+        }
+        Ok(profiles)
+    }
+}
+
+impl std::fmt::Display for Profiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "connect4-profiles {FORMAT_VERSION}")?;
+        for profile in &self.profiles {
+            writeln!(f)?;
+            writeln!(f, "[{}]", profile.name)?;
+            writeln!(f, "color {}", profile.color)?;
+            for (opponent, record) in &profile.opponents {
+                writeln!(
+                    f,
+                    "vs {opponent} = {} {} {}",
+                    record.wins, record.losses, record.draws
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILES: &str = "connect4-profiles 1
+
+[alice]
+color blue
+vs AI (hard) = 3 1 0
+vs bob = 2 2 1
+
+[bob]
+color yellow
+vs AI (easy) = 0 0 2
+vs alice = 2 2 1
+";
+
+    /// Get the reason the profiles could not be read.
+    fn invalid(s: &str) -> String {
+        match s.parse::<Profiles>() {
+            Err(Error::InvalidProfiles(reason)) => reason,
+            other => panic!("expected Error::InvalidProfiles, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn reads_what_it_writes() {
+        let profiles = PROFILES.parse::<Profiles>().unwrap();
+        assert_eq!(profiles.to_string(), PROFILES);
+
+        let alice = profiles.get("alice").unwrap();
+        assert_eq!(alice.color, Color::Blue);
+        assert_eq!(
+            alice.total(),
+            Record {
+                wins: 5,
+                losses: 3,
+                draws: 1
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        let duplicate = format!("{PROFILES}\n[alice]\n");
+        assert_eq!(invalid(&duplicate), "line 13: duplicate player 'alice'");
+        let missing_draws = "connect4-profiles 1\n[alice]\nvs bob = 1 2\n";
+        assert_eq!(
+            invalid(missing_draws),
+            "line 3: expected <wins> <losses> <draws>"
+        );
+        let missing_record = "connect4-profiles 1\n[alice]\nvs bob\n";
+        assert_eq!(
+            invalid(missing_record),
+            "line 3: expected 'vs <name> = <wins> <losses> <draws>'"
+        );
+        let missing_player = "connect4-profiles 1\nvs bob = 1 2 0\n";
+        assert_eq!(
+            invalid(missing_player),
+            "line 2: expected a player like [name]"
+        );
+    }
+
+    #[test]
+    fn ranks_players_with_and_without_profile() {
+        let profiles = PROFILES.parse::<Profiles>().unwrap();
+        let names = profiles
+            .leaderboard()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<String>>();
+        // both ai levels have 2 points, the one with fewer games played ranks higher
+        assert_eq!(names, ["alice", "bob", "AI (easy)", "AI (hard)"]);
+        assert_eq!(
+            profiles.leaderboard()[3].1,
+            Record {
+                wins: 1,
+                losses: 3,
+                draws: 0
+            }
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use super::{
+    bitboard::BitBoard,
+    board::Board,
+    components::{CheckWin, TileType},
+    error::GameResult,
+};
+
+/// Board the ai tries its moves on.
+///
+/// Uses a bitboard when the board is small enough to fit in one, and falls back to the tiles of
+/// the board otherwise. Only one of them is kept, so every move is only played once.
+#[derive(Debug, Clone)]
+pub(crate) enum SearchBoard {
+    Bits(BitBoard),
+    Tiles(Board),
+}
+
+impl SearchBoard {
+    /// Place a tile in the given column. The column is 1 indexed.
+    ///
+    /// # Errors
+    ///
+    /// See [`Board::place`].
+    pub(crate) fn place(&mut self, col: usize, tile: TileType) -> GameResult<()> {
+        match self {
+            Self::Bits(bits) => bits.place(col, tile),
+            Self::Tiles(tiles) => tiles.place(col, tile),
+        }
+    }
+
+    /// Get the number of tiles a player has to connect to win.
+    pub(crate) fn connect(&self) -> usize {
+        match self {
+            Self::Bits(bits) => bits.connect(),
+            Self::Tiles(tiles) => tiles.connect(),
+        }
+    }
+
+    /// Get the number of columns.
+    pub(crate) fn width(&self) -> usize {
+        match self {
+            Self::Bits(bits) => bits.width(),
+            Self::Tiles(tiles) => tiles.width(),
+        }
+    }
+
+    /// Get the row of the first empty spot in the column. The column is zero indexed.
+    pub(crate) fn first_empty(&self, col: usize) -> GameResult<usize> {
+        match self {
+            Self::Bits(bits) => bits.first_empty(col),
+            Self::Tiles(tiles) => tiles.first_empty(col),
+        }
+    }
+
+    /// Check if the last move connected enough tiles to win. See [`Board::check_win`].
+    pub(crate) fn check_win(&self) -> CheckWin {
+        match self {
+            Self::Bits(bits) => bits.check_win(),
+            Self::Tiles(tiles) => tiles.check_win(),
+        }
+    }
+
+    /// Get the type of tile at position (x, y).
+    pub(crate) fn get(&self, x: usize, y: usize) -> GameResult<TileType> {
+        match self {
+            Self::Bits(bits) => bits.get(x, y),
+            Self::Tiles(tiles) => tiles.get(x, y),
+        }
+    }
+
+    /// Undo the last move.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::NoUndos` if no moves have been made on this board.
+    pub(crate) fn undo_last(&mut self) -> GameResult<()> {
+        match self {
+            Self::Bits(bits) => bits.undo_last(),
+            Self::Tiles(tiles) => tiles.undo_last(),
+        }
+    }
+}
+
+/// Copy the tiles of the board.
+impl From<&Board> for SearchBoard {
+    fn from(board: &Board) -> Self {
+        match BitBoard::from_board(board) {
+            Some(bits) => Self::Bits(bits),
+            None => Self::Tiles(board.clone()),
+        }
+    }
+}
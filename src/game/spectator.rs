@@ -0,0 +1,383 @@
+use std::{
+    io::Write,
+    net::{
+        IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs,
+    },
+    sync::{
+        mpsc::{channel, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::game::{
+    components::{Rules, TileType},
+    error::GameResult,
+};
+
+/// Something that happened in the game, sent to spectators.
+///
+/// Columns are 1 indexed from the left, rows are 1 indexed from the bottom.
+#[derive(Debug, Clone)]
+pub(super) enum Event {
+    /// A new round started on a board of the given size. Contains the position the round
+    /// started from, if the board was not empty.
+    Round {
+        width: usize,
+        height: usize,
+        connect: usize,
+        rules: Rules,
+        position: Option<String>,
+        players: [String; 2],
+    },
+    /// A tile was placed.
+    Move {
+        tile: TileType,
+        name: String,
+        col: usize,
+        row: usize,
+    },
+    /// A tile was popped out of the bottom of a column.
+    Pop {
+        tile: TileType,
+        name: String,
+        col: usize,
+    },
+    /// A tile was taken back.
+    Undo { col: usize, row: usize },
+    /// A pop was taken back, putting the tile back at the bottom of the column.
+    Unpop { tile: TileType, col: usize },
+    /// A player connected one or more lines. Contains the positions of the tiles of every line
+    /// as (col, row).
+    Win {
+        tile: TileType,
+        name: String,
+        lines: Vec<Vec<(usize, usize)>>,
+    },
+    /// The board is full without a winner.
+    Draw,
+    /// The scores changed.
+    Score { scores: [(String, usize); 2] },
+}
+
+impl Event {
+    /// Encode the event as a single line of JSON.
+    fn to_json(&self) -> String {
+        match self {
+            Event::Round {
+                width,
+                height,
+                connect,
+                rules,
+                position,
+                players,
+            } => format!(
+                r#"{{"event":"round","width":{width},"height":{height},"connect":{connect},"rules":"{rules}","position":{},"players":[{},{}]}}"#,
+                position.as_deref().map_or(String::from("null"), json_str),
+                json_str(&players[0]),
+                json_str(&players[1])
+            ),
+            Event::Move {
+                tile,
+                name,
+                col,
+                row,
+            } => format!(
+                r#"{{"event":"move","player":{},"name":{},"column":{col},"row":{row}}}"#,
+                player(*tile),
+                json_str(name)
+            ),
+            Event::Pop { tile, name, col } => format!(
+                r#"{{"event":"pop","player":{},"name":{},"column":{col}}}"#,
+                player(*tile),
+                json_str(name)
+            ),
+            Event::Undo { col, row } => {
+                format!(r#"{{"event":"undo","column":{col},"row":{row}}}"#)
+            }
+            Event::Unpop { tile, col } => format!(
+                r#"{{"event":"unpop","player":{},"column":{col}}}"#,
+                player(*tile)
+            ),
+            Event::Win { tile, name, lines } => {
+                let cells = |cells: &[(usize, usize)]| {
+                    cells
+                        .iter()
+                        .map(|(col, row)| format!(r#"{{"column":{col},"row":{row}}}"#))
+                        .collect::<Vec<String>>()
+                        .join(",")
+                };
+                // lines crossing each other share a tile
+                let mut all = lines.concat();
+                all.sort();
+                all.dedup();
+                format!(
+                    r#"{{"event":"win","player":{},"name":{},"cells":[{}],"lines":[{}]}}"#,
+                    player(*tile),
+                    json_str(name),
+                    cells(&all),
+                    lines
+                        .iter()
+                        .map(|line| format!("[{}]", cells(line)))
+                        .collect::<Vec<String>>()
+                        .join(",")
+                )
+            }
+            Event::Draw => String::from(r#"{"event":"draw"}"#),
+            Event::Score { scores } => format!(
+                r#"{{"event":"score","scores":[{}]}}"#,
+                scores
+                    .iter()
+                    .map(|(name, score)| format!(
+                        r#"{{"name":{},"score":{score}}}"#,
+                        json_str(name)
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+        }
+    }
+}
+
+/// Number of the player with the given tile.
+fn player(tile: TileType) -> usize {
+    match tile {
+        TileType::Player1 => 1,
+        TileType::Player2 => 2,
+        TileType::Empty => 0,
+    }
+}
+
+/// Encode a string as a JSON string.
+fn json_str(s: &str) -> String {
+    let mut json = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Spectators taking longer than this to receive an event are dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Connected spectators, and what they need to catch up when joining.
+#[derive(Debug, Default)]
+struct Audience {
+    /// Lines to send to every spectator, written by a thread per spectator.
+    clients: Vec<Sender<String>>,
+    /// Events since the start of the current round.
+    round: Vec<String>,
+    /// The last score event.
+    score: Option<String>,
+    /// If the endpoint was closed. Stops accepting new spectators.
+    closed: bool,
+}
+
+/// Read only endpoint streaming the game to any number of spectators.
+///
+/// Every event is sent as a line of JSON. Spectators joining halfway through a round first
+/// receive the events they missed. Nothing sent by spectators is read.
+///
+/// Every spectator gets a thread writing the events to it, so a slow spectator doesn't hold up
+/// the game or the other spectators.
+#[derive(Debug)]
+pub(super) struct Spectators {
+    audience: Arc<Mutex<Audience>>,
+    /// Address the spectators connect to.
+    addr: SocketAddr,
+    /// Thread accepting new spectators, stopped when dropping.
+    accept: Option<JoinHandle<()>>,
+}
+
+impl Spectators {
+    /// Start accepting spectators on the given address.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Io` if the address can't be bound.
+    pub(super) fn new(addr: impl ToSocketAddrs) -> GameResult<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let audience = Arc::new(Mutex::new(Audience::default()));
+        log::info!("Spectators: watching on {addr}");
+
+        let audience_thread = audience.clone();
+        let accept = thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        log::warn!("Spectators: {err}");
+                        continue;
+                    }
+                };
+                let mut audience = audience_thread.lock().unwrap();
+                if audience.closed {
+                    break;
+                }
+                // spectators can only watch
+                drop(stream.shutdown(Shutdown::Read)); // drop to discard result without warning
+                if let Err(err) = stream.set_write_timeout(Some(WRITE_TIMEOUT)) {
+                    log::warn!("Spectators: {err}");
+                    continue;
+                }
+
+                let (sender, events) = channel::<String>();
+                let missed = audience.score.iter().chain(audience.round.iter());
+                for event in missed {
+                    drop(sender.send(event.clone())); // the receiver is still here
+                }
+                audience.clients.push(sender);
+
+                let peer = stream.peer_addr();
+                log::info!("Spectators: {peer:?} joined");
+                thread::spawn(move || {
+                    for event in events {
+                        if let Err(err) = writeln!(stream, "{event}") {
+                            log::info!("Spectators: dropped {peer:?}, {err}");
+                            break;
+                        }
+                    }
+                    // drop to discard result without warning
+                    drop(stream.shutdown(Shutdown::Both));
+                });
+            }
+        });
+
+        Ok(Self {
+            audience,
+            addr,
+            accept: Some(accept),
+        })
+    }
+
+    /// Send an event to all spectators. Spectators that couldn't be reached before are dropped.
+    pub(super) fn send(&self, event: &Event) {
+        let json = event.to_json();
+        let mut audience = self.audience.lock().unwrap();
+        // the thread of a spectator stops when writing to it fails
+        audience
+            .clients
+            .retain(|client| client.send(json.clone()).is_ok());
+
+        match event {
+            Event::Round { .. } => audience.round = vec![json],
+            Event::Score { .. } => audience.score = Some(json),
+            _ => audience.round.push(json),
+        }
+    }
+}
+
+impl Drop for Spectators {
+    fn drop(&mut self) {
+        {
+            let mut audience = self.audience.lock().unwrap();
+            audience.closed = true;
+            // the threads of the spectators hang up after sending what is left
+            audience.clients.clear();
+        }
+
+        // wake up the accept thread with a connection of our own, so it sees the endpoint
+        // closed and releases the address
+        let mut addr = self.addr;
+        match addr.ip() {
+            IpAddr::V4(ip) if ip.is_unspecified() => addr.set_ip(Ipv4Addr::LOCALHOST.into()),
+            IpAddr::V6(ip) if ip.is_unspecified() => addr.set_ip(Ipv6Addr::LOCALHOST.into()),
+            _ => (),
+        }
+        match TcpStream::connect_timeout(&addr, WRITE_TIMEOUT) {
+            Ok(_) => {
+                if let Some(accept) = self.accept.take() {
+                    drop(accept.join()); // drop to discard result without warning
+                }
+            }
+            Err(err) => log::warn!("Spectators: can't stop accepting, {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Lines};
+
+    use super::*;
+
+    /// Connect a spectator, and wait until it is in the audience.
+    fn watch(spectators: &Spectators) -> Lines<BufReader<TcpStream>> {
+        let joined = spectators.audience.lock().unwrap().clients.len() + 1;
+        let stream = TcpStream::connect(spectators.addr).unwrap();
+        while spectators.audience.lock().unwrap().clients.len() < joined {
+            thread::sleep(Duration::from_millis(1));
+        }
+        BufReader::new(stream).lines()
+    }
+
+    #[test]
+    fn streams_moves_and_wins() {
+        let spectators = Spectators::new("127.0.0.1:0").unwrap();
+        let mut clients = [watch(&spectators), watch(&spectators)];
+
+        spectators.send(&Event::Move {
+            tile: TileType::Player1,
+            name: String::from("a"),
+            col: 4,
+            row: 1,
+        });
+        spectators.send(&Event::Win {
+            tile: TileType::Player2,
+            name: String::from("b \"the\" bot"),
+            lines: vec![vec![(1, 1), (2, 2), (3, 3)], vec![(3, 1), (3, 2), (3, 3)]],
+        });
+
+        for client in &mut clients {
+            assert_eq!(
+                client.next().unwrap().unwrap(),
+                r#"{"event":"move","player":1,"name":"a","column":4,"row":1}"#
+            );
+            assert_eq!(
+                client.next().unwrap().unwrap(),
+                concat!(
+                    r#"{"event":"win","player":2,"name":"b \"the\" bot","#,
+                    r#""cells":[{"column":1,"row":1},{"column":2,"row":2},{"column":3,"row":1},"#,
+                    r#"{"column":3,"row":2},{"column":3,"row":3}],"#,
+                    r#""lines":[[{"column":1,"row":1},{"column":2,"row":2},{"column":3,"row":3}],"#,
+                    r#"[{"column":3,"row":1},{"column":3,"row":2},{"column":3,"row":3}]]}"#
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn late_spectators_catch_up() {
+        let spectators = Spectators::new("127.0.0.1:0").unwrap();
+        spectators.send(&Event::Score {
+            scores: [(String::from("a"), 1), (String::from("b"), 0)],
+        });
+        spectators.send(&Event::Draw);
+
+        let mut client = watch(&spectators);
+        let score = r#"{"event":"score","scores":[{"name":"a","score":1},{"name":"b","score":0}]}"#;
+        assert_eq!(client.next().unwrap().unwrap(), score);
+        assert_eq!(client.next().unwrap().unwrap(), r#"{"event":"draw"}"#);
+    }
+
+    #[test]
+    fn releases_the_address() {
+        let spectators = Spectators::new("127.0.0.1:0").unwrap();
+        let addr = spectators.addr;
+        let mut client = watch(&spectators);
+        drop(spectators);
+
+        // the spectator is hung up on, and the address can be bound again
+        assert!(client.next().is_none());
+        drop(Spectators::new(addr).unwrap());
+    }
+}
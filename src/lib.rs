@@ -2,5 +2,19 @@
 #![warn(missing_docs)]
 //! Connect 4 game crate
 pub(crate) mod ai;
+pub mod cli;
+pub(crate) mod display;
 pub(crate) mod game;
-pub use game::Game;
+pub use ai::AiLevel;
+pub use display::{
+    Animation, AnimationFrame, Blink, Color, DisplayEvent, Led, MatrixDisplay, RecordingDisplay,
+    TerminalDisplay,
+};
+#[doc(hidden)]
+pub use game::bench;
+pub use game::{
+    Action, AiPlayer, BoardView, Button, ButtonPins, ButtonScript, ButtonSource, Buttons, Config,
+    DisplayPins, Error, Game, GameRecord, LocalPlayer, Move, NetworkPlayer, Outcome,
+    PlayerController, Profile, Profiles, Record, Rules, SavedGame, SimulatedButton, TileType, Turn,
+    BOARD_SIZES, DEFAULT_CONNECT, DEFAULT_PORT, PROTOCOL_VERSION,
+};
@@ -1,12 +1,9 @@
-use connect4::Game;
-const W: usize = 7;
-const H: usize = 7;
+//! Connect 4 in the terminal.
+//!
+//! Usage: `connect4 [options]`, run with `--help` to list the options. The game on the led
+//! matrix is built from the `matrix` package.
+use connect4::cli::{self, TerminalScreen};
+
 fn main() {
-    env_logger::builder()
-        .filter_level(log::LevelFilter::Debug)
-        .init();
-    let mut game = Game::<W, H>::new().unwrap();
-    game.enable_display();
-    game.enable_buttons().unwrap();
-    game.start();
+    cli::main(TerminalScreen);
 }
@@ -0,0 +1,32 @@
+//! Play the terminal game from the command line, reading the moves from stdin.
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+#[test]
+fn plays_headless() {
+    // the profiles are kept in the working directory
+    let dir = std::env::temp_dir().join(format!("connect4-headless-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut game = Command::new(env!("CARGO_BIN_EXE_connect4"))
+        .arg("--headless")
+        .current_dir(&dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    game.stdin
+        .take()
+        .unwrap()
+        .write_all(b"y\n4\n3\n4\n3\n4\n3\n4\nn\nq\n")
+        .unwrap();
+    let output = game.wait_with_output().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("a wins"), "{stdout}");
+    assert!(stdout.contains("a's score: 1\tb's score: 0"), "{stdout}");
+}